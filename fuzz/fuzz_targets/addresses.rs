@@ -0,0 +1,24 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Parses arbitrary strings as z-base-32 encoded addresses, and bytes as keys and signatures.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use sn_data_types::{BlobAddress, MapAddress, PublicKey, SequenceAddress, Signature};
+
+fuzz_target!(|bytes: &[u8]| {
+    if let Ok(encoded) = std::str::from_utf8(bytes) {
+        let _ = BlobAddress::decode_from_zbase32(encoded);
+        let _ = MapAddress::decode_from_zbase32(encoded);
+        let _ = SequenceAddress::decode_from_zbase32(encoded);
+    }
+    let _ = bincode::deserialize::<PublicKey>(bytes);
+    let _ = bincode::deserialize::<Signature>(bytes);
+});
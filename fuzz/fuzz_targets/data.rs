@@ -0,0 +1,31 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Deserialises arbitrary bytes as the data types nodes receive from the network.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use sn_data_types::{
+    Blob, Data, Map, MapEntryActions, Sequence, SequenceBatchOp, SequenceEntry, SequenceOp,
+    MAX_BLOB_SIZE_IN_BYTES,
+};
+
+fuzz_target!(|bytes: &[u8]| {
+    // Nodes reject messages larger than a chunk before deserialising them.
+    if bytes.len() as u64 > MAX_BLOB_SIZE_IN_BYTES {
+        return;
+    }
+    let _ = bincode::deserialize::<Data>(bytes);
+    let _ = bincode::deserialize::<Blob>(bytes);
+    let _ = bincode::deserialize::<Map>(bytes);
+    let _ = bincode::deserialize::<MapEntryActions>(bytes);
+    let _ = bincode::deserialize::<Sequence>(bytes);
+    let _ = bincode::deserialize::<SequenceOp<SequenceEntry>>(bytes);
+    let _ = bincode::deserialize::<SequenceBatchOp<SequenceEntry>>(bytes);
+});
@@ -0,0 +1,29 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Deserialises arbitrary bytes as transfers and Replica events, and verifies what parses.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use sn_data_types::{ReplicaEvent, SignedTransfer, TransferAgreementProof, MAX_BLOB_SIZE_IN_BYTES};
+use std::collections::BTreeSet;
+
+fuzz_target!(|bytes: &[u8]| {
+    if bytes.len() as u64 > MAX_BLOB_SIZE_IN_BYTES {
+        return;
+    }
+    let _ = bincode::deserialize::<SignedTransfer>(bytes);
+    let _ = bincode::deserialize::<TransferAgreementProof>(bytes);
+    if let Ok(event) = bincode::deserialize::<ReplicaEvent>(bytes) {
+        // Checking signatures of malformed events must fail cleanly too.
+        if let ReplicaEvent::TransferRegistered(registered) = &event {
+            let _ = event.verify(&registered.transfer_proof.replica_keys(), &BTreeSet::new());
+        }
+    }
+});
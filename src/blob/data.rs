@@ -0,0 +1,607 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{Address, Kind, StorageClass};
+use crate::{utils, Error, Keypair, PublicKey, Scope, Signature, XorName};
+use bincode::serialized_size;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    fmt::{self, Debug, Formatter},
+    u64,
+};
+use tiny_keccak::{Hasher, Sha3};
+
+/// Maximum allowed size for a serialised Blob to grow to.
+pub const MAX_BLOB_SIZE_IN_BYTES: u64 = 1024 * 1024 + 10 * 1024;
+
+/// Private Blob: an immutable chunk of data which can be deleted. Can only be fetched
+/// by the listed owner.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone)]
+pub struct PrivateData {
+    /// Network address. Omitted when serialising and calculated from the `value` and `owner` when
+    /// deserialising.
+    address: Address,
+    /// Contained data.
+    value: Vec<u8>,
+    /// Contains a set of owners of this data. DataManagers enforce that a DELETE or OWNED-GET type
+    /// of request is coming from the MaidManager Authority of the owners.
+    owner: PublicKey,
+    /// Storage hints, which do not affect the address.
+    storage_class: StorageClass,
+}
+
+impl PrivateData {
+    /// Creates a new instance of `PrivateData`, with the default storage class.
+    pub fn new(value: Vec<u8>, owner: PublicKey) -> Self {
+        Self {
+            address: Self::address_for(&value, &owner),
+            value,
+            owner,
+            storage_class: StorageClass::default(),
+        }
+    }
+
+    /// Sets the storage class.
+    pub fn with_storage_class(mut self, storage_class: StorageClass) -> Self {
+        self.storage_class = storage_class;
+        self
+    }
+
+    /// Returns the address a `PrivateData` with the given value and owner would have,
+    /// without constructing it.
+    pub fn address_for(value: &[u8], owner: &PublicKey) -> Address {
+        Address::Private(XorName::from_content(&[value, &owner.to_bytes()]))
+    }
+
+    /// Returns the value.
+    pub fn value(&self) -> &Vec<u8> {
+        &self.value
+    }
+
+    /// Returns the set of owners.
+    pub fn owner(&self) -> &PublicKey {
+        &self.owner
+    }
+
+    /// Returns the storage class.
+    pub fn storage_class(&self) -> StorageClass {
+        self.storage_class
+    }
+
+    /// Returns the address.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &XorName {
+        self.address.name()
+    }
+
+    /// Returns size of contained value.
+    pub fn payload_size(&self) -> usize {
+        self.value.len()
+    }
+
+    /// Returns size of this data after serialisation.
+    pub fn serialised_size(&self) -> u64 {
+        serialized_size(&self.serialised_structure()).unwrap_or(u64::MAX)
+    }
+
+    /// Returns `true` if the size is valid.
+    pub fn validate_size(&self) -> bool {
+        self.serialised_size() <= MAX_BLOB_SIZE_IN_BYTES
+    }
+
+    fn serialised_structure(&self) -> (&[u8], &PublicKey, StorageClass) {
+        (&self.value, &self.owner, self.storage_class)
+    }
+}
+
+impl Serialize for PrivateData {
+    fn serialize<S: Serializer>(&self, serialiser: S) -> Result<S::Ok, S::Error> {
+        // Address is omitted since it's derived from value + owner
+        self.serialised_structure().serialize(serialiser)
+    }
+}
+
+impl<'de> Deserialize<'de> for PrivateData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (value, owner, storage_class) = Deserialize::deserialize(deserializer)?;
+        Ok(Self::new(value, owner).with_storage_class(storage_class))
+    }
+}
+
+impl Debug for PrivateData {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        // TODO: Output owners?
+        write!(formatter, "PrivateBlob {:?}", self.name())
+    }
+}
+
+/// Public Blob: an immutable chunk of data which cannot be deleted.
+#[derive(Hash, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct PublicData {
+    /// Network address. Omitted when serialising and calculated from the `value` when
+    /// deserialising.
+    address: Address,
+    /// Contained data.
+    value: Vec<u8>,
+    /// Storage hints, which do not affect the address.
+    storage_class: StorageClass,
+}
+
+impl PublicData {
+    /// Creates a new instance of `Blob`, with the default storage class.
+    pub fn new(value: Vec<u8>) -> Self {
+        Self {
+            address: Self::address_for(&value),
+            value,
+            storage_class: StorageClass::default(),
+        }
+    }
+
+    /// Sets the storage class.
+    pub fn with_storage_class(mut self, storage_class: StorageClass) -> Self {
+        self.storage_class = storage_class;
+        self
+    }
+
+    /// Returns the address a `Blob` with the given value would have, without constructing it.
+    pub fn address_for(value: &[u8]) -> Address {
+        Address::Public(XorName::from_content(&[value]))
+    }
+
+    /// Returns the value.
+    pub fn value(&self) -> &Vec<u8> {
+        &self.value
+    }
+
+    /// Returns the storage class.
+    pub fn storage_class(&self) -> StorageClass {
+        self.storage_class
+    }
+
+    /// Returns the address.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &XorName {
+        self.address.name()
+    }
+
+    /// Returns size of contained value.
+    pub fn payload_size(&self) -> usize {
+        self.value.len()
+    }
+
+    /// Returns size of this data after serialisation.
+    pub fn serialised_size(&self) -> u64 {
+        serialized_size(self).unwrap_or(u64::MAX)
+    }
+
+    /// Returns true if the size is valid.
+    pub fn validate_size(&self) -> bool {
+        self.serialised_size() <= MAX_BLOB_SIZE_IN_BYTES
+    }
+}
+
+impl Serialize for PublicData {
+    fn serialize<S: Serializer>(&self, serialiser: S) -> Result<S::Ok, S::Error> {
+        (&self.value, self.storage_class).serialize(serialiser)
+    }
+}
+
+impl<'de> Deserialize<'de> for PublicData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (value, storage_class): (Vec<u8>, StorageClass) =
+            Deserialize::deserialize(deserializer)?;
+        Ok(PublicData::new(value).with_storage_class(storage_class))
+    }
+}
+
+impl Debug for PublicData {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "PublicBlob {:?}", self.name())
+    }
+}
+
+/// Object storing an Blob variant.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum Data {
+    /// Private Blob.
+    Private(PrivateData),
+    /// Public Blob.
+    Public(PublicData),
+}
+
+impl Data {
+    /// Returns the address a Blob of the given kind would have for the given value, without
+    /// constructing it. This allows checking whether the content already exists on the network
+    /// before uploading it.
+    ///
+    /// Returns `Err(InvalidOperation)` if the kind is private and no owner is provided.
+    pub fn address_for(
+        value: &[u8],
+        kind: Kind,
+        owner: Option<&PublicKey>,
+    ) -> Result<Address, Error> {
+        match (kind, owner) {
+            (Kind::Pub, _) => Ok(PublicData::address_for(value)),
+            (Kind::Private, Some(owner)) => Ok(PrivateData::address_for(value, owner)),
+            (Kind::Private, None) => Err(Error::InvalidOperation),
+        }
+    }
+
+    /// Returns the address.
+    pub fn address(&self) -> &Address {
+        match self {
+            Data::Private(data) => data.address(),
+            Data::Public(data) => data.address(),
+        }
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &XorName {
+        self.address().name()
+    }
+
+    /// Returns the owner if private blob.
+    pub fn owner(&self) -> Option<&PublicKey> {
+        match self {
+            Data::Private(data) => Some(data.owner()),
+            _ => None,
+        }
+    }
+
+    /// Returns the kind.
+    pub fn kind(&self) -> Kind {
+        self.address().kind()
+    }
+
+    /// Returns the scope.
+    pub fn scope(&self) -> Scope {
+        self.address().scope()
+    }
+
+    /// Returns true if published.
+    pub fn is_public(&self) -> bool {
+        self.kind().is_public()
+    }
+
+    /// Returns true if unpublished.
+    pub fn is_private(&self) -> bool {
+        self.kind().is_private()
+    }
+
+    /// Returns the value.
+    pub fn value(&self) -> &Vec<u8> {
+        match self {
+            Data::Private(data) => data.value(),
+            Data::Public(data) => data.value(),
+        }
+    }
+
+    /// Returns the storage class.
+    pub fn storage_class(&self) -> StorageClass {
+        match self {
+            Data::Private(data) => data.storage_class(),
+            Data::Public(data) => data.storage_class(),
+        }
+    }
+
+    /// Returns `true` if the size is valid.
+    pub fn validate_size(&self) -> bool {
+        match self {
+            Data::Private(data) => data.validate_size(),
+            Data::Public(data) => data.validate_size(),
+        }
+    }
+
+    /// Returns size of this data after serialisation.
+    pub fn serialised_size(&self) -> u64 {
+        match self {
+            Data::Private(data) => data.serialised_size(),
+            Data::Public(data) => data.serialised_size(),
+        }
+    }
+}
+
+impl From<PrivateData> for Data {
+    fn from(data: PrivateData) -> Self {
+        Data::Private(data)
+    }
+}
+
+impl From<PublicData> for Data {
+    fn from(data: PublicData) -> Self {
+        Data::Public(data)
+    }
+}
+
+/// Nonce sent by an Elder to challenge an Adult to prove it holds a chunk.
+pub type ChallengeNonce = [u8; 32];
+
+/// Proof that an Adult holds a chunk: the hash of the chunk's value and the challenge nonce,
+/// signed by the Adult. The hash can only be computed by someone holding the full value.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct ChunkProof {
+    /// Address of the challenged chunk.
+    pub address: Address,
+    /// SHA3-256 hash of the chunk's value followed by the nonce.
+    pub hash: [u8; 32],
+    /// Key of the Adult holding the chunk.
+    pub holder: PublicKey,
+    /// Signature of the Adult over the address and the hash.
+    pub signature: Signature,
+}
+
+impl ChunkProof {
+    /// Answers a challenge for the given chunk, signing with the Adult's keypair.
+    pub fn new(chunk: &Data, nonce: &ChallengeNonce, keypair: &Keypair) -> Result<Self, Error> {
+        let address = *chunk.address();
+        let hash = Self::hash(chunk.value(), nonce);
+        let bytes = utils::serialise(&(address, hash))?;
+        Ok(Self {
+            address,
+            hash,
+            holder: keypair.public_key(),
+            signature: keypair.sign(&bytes),
+        })
+    }
+
+    /// Verifies the proof against the chunk and nonce held by the challenger, and the key
+    /// of the Adult it challenged. Returns `Err(InvalidOperation)` if the proof is for
+    /// another chunk or nonce, and `Err(InvalidSignature)` if it was not signed by `holder`.
+    pub fn verify(
+        &self,
+        chunk: &Data,
+        nonce: &ChallengeNonce,
+        holder: &PublicKey,
+    ) -> Result<(), Error> {
+        if &self.address != chunk.address() || self.hash != Self::hash(chunk.value(), nonce) {
+            return Err(Error::InvalidOperation);
+        }
+        if &self.holder != holder {
+            return Err(Error::InvalidSignature);
+        }
+        let bytes = utils::serialise(&(self.address, self.hash))?;
+        self.holder.verify(&self.signature, &bytes)
+    }
+
+    fn hash(value: &[u8], nonce: &ChallengeNonce) -> [u8; 32] {
+        let mut hasher = Sha3::v256();
+        let mut output = [0; 32];
+        hasher.update(value);
+        hasher.update(nonce);
+        hasher.finalize(&mut output);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Address, ChunkProof, Data, Kind, PrivateData, PublicData, PublicKey, StorageClass, XorName,
+    };
+    use crate::{utils, DataAddress, Error, Keypair, Result, Scope};
+    use hex::encode;
+    use rand::{self, Rng, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+    use std::{env, iter, thread};
+    use threshold_crypto::SecretKey;
+
+    #[test]
+    fn deterministic_name() {
+        let data1 = b"Hello".to_vec();
+        let data2 = b"Goodbye".to_vec();
+
+        let owner1 = PublicKey::Bls(SecretKey::random().public_key());
+        let owner2 = PublicKey::Bls(SecretKey::random().public_key());
+
+        let idata1 = PrivateData::new(data1.clone(), owner1);
+        let idata2 = PrivateData::new(data1, owner2);
+        let idata3 = PrivateData::new(data2.clone(), owner1);
+        let idata3_clone = PrivateData::new(data2, owner1);
+
+        assert_eq!(idata3, idata3_clone);
+
+        assert_ne!(idata1.name(), idata2.name());
+        assert_ne!(idata1.name(), idata3.name());
+        assert_ne!(idata2.name(), idata3.name());
+    }
+
+    #[test]
+    fn address_for_matches_constructed_blob() -> Result<()> {
+        let value = b"Hello".to_vec();
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+
+        let public = PublicData::new(value.clone());
+        let private = PrivateData::new(value.clone(), owner);
+
+        assert_eq!(public.address(), &PublicData::address_for(&value));
+        assert_eq!(private.address(), &PrivateData::address_for(&value, &owner));
+        assert_eq!(
+            public.address(),
+            &Data::address_for(&value, Kind::Pub, None)?
+        );
+        assert_eq!(
+            private.address(),
+            &Data::address_for(&value, Kind::Private, Some(&owner))?
+        );
+        assert!(Data::address_for(&value, Kind::Private, None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn chunk_proof_verifies() -> Result<()> {
+        let keypair = Keypair::new_ed25519(&mut rand::thread_rng());
+        let chunk = Data::from(PublicData::new(b"Hello".to_vec()));
+        let other_chunk = Data::from(PublicData::new(b"Goodbye".to_vec()));
+        let nonce = rand::random();
+
+        let holder = keypair.public_key();
+        let proof = ChunkProof::new(&chunk, &nonce, &keypair)?;
+        proof.verify(&chunk, &nonce, &holder)?;
+        assert_eq!(
+            proof.verify(&chunk, &rand::random(), &holder),
+            Err(Error::InvalidOperation)
+        );
+        assert_eq!(
+            proof.verify(&other_chunk, &nonce, &holder),
+            Err(Error::InvalidOperation)
+        );
+
+        let other = Keypair::new_ed25519(&mut rand::thread_rng());
+        let forged = ChunkProof {
+            holder: other.public_key(),
+            ..proof
+        };
+        assert_eq!(
+            forged.verify(&chunk, &nonce, &other.public_key()),
+            Err(Error::InvalidSignature)
+        );
+
+        // Another node holding the chunk cannot answer a challenge sent to the Adult.
+        let answered_by_other = ChunkProof::new(&chunk, &nonce, &other)?;
+        answered_by_other.verify(&chunk, &nonce, &other.public_key())?;
+        assert_eq!(
+            answered_by_other.verify(&chunk, &nonce, &holder),
+            Err(Error::InvalidSignature)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn storage_class_bounds() -> Result<()> {
+        assert!(StorageClass::default().is_default());
+        assert_eq!(StorageClass::new(8)?.replication(), 8);
+        assert_eq!(StorageClass::new(0), Err(Error::InvalidOperation));
+        assert_eq!(
+            StorageClass::new(StorageClass::MAX_REPLICATION + 1),
+            Err(Error::InvalidOperation)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn storage_class_of_blobs() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let class = StorageClass::new(8)?;
+        let public = PublicData::new(b"data".to_vec());
+        let private = PrivateData::new(b"data".to_vec(), owner);
+        assert!(public.storage_class().is_default());
+        assert!(private.storage_class().is_default());
+
+        // The storage class is kept through serialisation, but does not change the address.
+        for blob in &[
+            Data::from(public.clone().with_storage_class(class)),
+            Data::from(private.clone().with_storage_class(class)),
+        ] {
+            let copy: Data = utils::deserialise(&utils::serialise(blob)?)?;
+            assert_eq!(copy.storage_class(), class);
+            assert_eq!(&copy, blob);
+        }
+        assert_eq!(
+            public.with_storage_class(class).address(),
+            PublicData::new(b"data".to_vec()).address()
+        );
+
+        let out_of_bounds = utils::serialise(&(b"data".to_vec(), 0_u8))?;
+        assert!(utils::deserialise::<PublicData>(&out_of_bounds).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn scope_matches_kind() {
+        let value = b"Hello".to_vec();
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+
+        let public = Data::from(PublicData::new(value.clone()));
+        let private = Data::from(PrivateData::new(value, owner));
+
+        assert_eq!(public.scope(), Scope::Public);
+        assert_eq!(public.address().scope(), Scope::Public);
+        assert_eq!(private.scope(), Scope::Private);
+        assert_eq!(private.address().scope(), Scope::Private);
+    }
+
+    #[test]
+    fn address_display() {
+        let name = XorName([0xab; 32]);
+        assert_eq!(Address::Public(name).to_string(), "PublicBlob(ababab..)");
+        assert_eq!(
+            DataAddress::from(Address::Private(name)).to_string(),
+            "PrivateBlob(ababab..)"
+        );
+    }
+
+    #[test]
+    fn deterministic_test() {
+        let value = "immutable data value".to_owned().into_bytes();
+        let blob = PublicData::new(value);
+        let blob_name = encode(blob.name().0.as_ref());
+        let expected_name = "fac2869677ee06277633c37ac7e8e5c655f3d652f707c7a79fab930d584a3016";
+
+        assert_eq!(&expected_name, &blob_name);
+    }
+
+    #[test]
+    fn serialisation() -> Result<()> {
+        let mut rng = get_rng();
+        let len = rng.gen_range(1, 10_000);
+        let value = iter::repeat_with(|| rng.gen()).take(len).collect();
+        let blob = PublicData::new(value);
+        let serialised = utils::serialise(&blob)?;
+        let parsed = utils::deserialise(&serialised)?;
+        assert_eq!(blob, parsed);
+        Ok(())
+    }
+
+    fn get_rng() -> XorShiftRng {
+        let env_var_name = "RANDOM_SEED";
+        let seed = env::var(env_var_name)
+            .map(|res| res.parse::<u64>().unwrap_or_else(|_| rand::random()))
+            .unwrap_or_else(|_| rand::random());
+        println!(
+            "To replay this '{}', set env var {}={}",
+            thread::current().name().unwrap_or(""),
+            env_var_name,
+            seed
+        );
+        XorShiftRng::seed_from_u64(seed)
+    }
+
+    #[test]
+    fn zbase32_encode_decode_idata_address() -> Result<()> {
+        let name = XorName::random();
+        let address = Address::Public(name);
+        let encoded = address.encode_to_zbase32()?;
+        let decoded = Address::decode_from_zbase32(&encoded)?;
+        assert_eq!(address, decoded);
+        Ok(())
+    }
+
+    #[test]
+    fn address_constructors() {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let value = b"value";
+
+        let public = Address::from_name_public(*PublicData::address_for(value).name());
+        assert_eq!(public, *PublicData::new(value.to_vec()).address());
+        assert!(public.is_public());
+
+        let private = Address::from_name_private(*PrivateData::address_for(value, &owner).name());
+        assert_eq!(private, *PrivateData::new(value.to_vec(), owner).address());
+        assert!(private.is_private());
+        assert_ne!(private, Address::from_name_public(*private.name()));
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{utils, Error, Scope, XorName};
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+};
+
+/// Kind of an Blob.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum Kind {
+    /// Private.
+    Private,
+    /// Public.
+    Pub,
+}
+
+impl Kind {
+    /// Creates `Kind` from a `published` flag.
+    pub fn from_flag(published: bool) -> Self {
+        if published {
+            Kind::Pub
+        } else {
+            Kind::Private
+        }
+    }
+
+    /// Returns true if published.
+    pub fn is_public(self) -> bool {
+        self == Kind::Pub
+    }
+
+    /// Returns true if unpublished.
+    pub fn is_private(self) -> bool {
+        !self.is_public()
+    }
+}
+
+impl From<Kind> for Scope {
+    fn from(kind: Kind) -> Self {
+        match kind {
+            Kind::Pub => Scope::Public,
+            Kind::Private => Scope::Private,
+        }
+    }
+}
+
+/// Address of an Blob.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum Address {
+    /// Private namespace.
+    Private(XorName),
+    /// Public namespace.
+    Public(XorName),
+}
+
+impl Address {
+    /// Constructs an `Address` given `kind` and `name`.
+    pub fn from_kind(kind: Kind, name: XorName) -> Self {
+        match kind {
+            Kind::Pub => Address::Public(name),
+            Kind::Private => Address::Private(name),
+        }
+    }
+
+    /// Constructs the address of a public Blob with the given name.
+    pub fn from_name_public(name: XorName) -> Self {
+        Address::Public(name)
+    }
+
+    /// Constructs the address of a private Blob with the given name. The name of a private
+    /// Blob already accounts for its owner, see `PrivateData::address_for`.
+    pub fn from_name_private(name: XorName) -> Self {
+        Address::Private(name)
+    }
+
+    /// Returns the kind.
+    pub fn kind(&self) -> Kind {
+        match self {
+            Address::Private(_) => Kind::Private,
+            Address::Public(_) => Kind::Pub,
+        }
+    }
+
+    /// Returns the scope.
+    pub fn scope(&self) -> Scope {
+        self.kind().into()
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &XorName {
+        match self {
+            Address::Private(ref name) | Address::Public(ref name) => name,
+        }
+    }
+
+    /// Returns true if published.
+    pub fn is_public(&self) -> bool {
+        self.kind().is_public()
+    }
+
+    /// Returns true if unpublished.
+    pub fn is_private(&self) -> bool {
+        self.kind().is_private()
+    }
+
+    /// Returns the Address serialised and encoded in z-base-32.
+    pub fn encode_to_zbase32(&self) -> Result<String, Error> {
+        utils::encode(&self)
+    }
+
+    /// Creates from z-base-32 encoded string.
+    pub fn decode_from_zbase32<T: AsRef<str>>(encoded: T) -> Result<Self, Error> {
+        utils::decode(encoded)
+    }
+}
+
+impl Display for Address {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Address::Private(name) => write!(formatter, "PrivateBlob({})", name),
+            Address::Public(name) => write!(formatter, "PublicBlob({})", name),
+        }
+    }
+}
+/// Storage hints for a Blob, e.g. how many copies of it the network should keep.
+///
+/// The address of a Blob does not depend on its storage class. The bounds are checked
+/// on deserialisation too.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+#[serde(try_from = "u8", into = "u8")]
+pub struct StorageClass {
+    replication: u8,
+}
+
+impl StorageClass {
+    /// Lowest number of copies which can be requested.
+    pub const MIN_REPLICATION: u8 = 1;
+    /// Highest number of copies which can be requested.
+    pub const MAX_REPLICATION: u8 = 16;
+    /// Number of copies kept by the network by default.
+    pub const DEFAULT_REPLICATION: u8 = 4;
+
+    /// Constructs a storage class with the given replication factor.
+    /// Returns `Err(InvalidOperation)` if it is out of bounds.
+    pub fn new(replication: u8) -> Result<Self, Error> {
+        if (Self::MIN_REPLICATION..=Self::MAX_REPLICATION).contains(&replication) {
+            Ok(Self { replication })
+        } else {
+            Err(Error::InvalidOperation)
+        }
+    }
+
+    /// Returns the number of copies to keep.
+    pub fn replication(&self) -> u8 {
+        self.replication
+    }
+
+    /// Returns `true` if this is the network-standard storage class.
+    pub fn is_default(&self) -> bool {
+        self.replication == Self::DEFAULT_REPLICATION
+    }
+}
+
+impl Default for StorageClass {
+    fn default() -> Self {
+        Self {
+            replication: Self::DEFAULT_REPLICATION,
+        }
+    }
+}
+
+impl TryFrom<u8> for StorageClass {
+    type Error = Error;
+
+    fn try_from(replication: u8) -> Result<Self, Error> {
+        Self::new(replication)
+    }
+}
+
+impl From<StorageClass> for u8 {
+    fn from(storage_class: StorageClass) -> Self {
+        storage_class.replication
+    }
+}
@@ -0,0 +1,16 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+#[cfg(feature = "blob")]
+mod data;
+mod metadata;
+
+#[cfg(feature = "blob")]
+pub use data::{ChallengeNonce, ChunkProof, Data, PrivateData, PublicData, MAX_BLOB_SIZE_IN_BYTES};
+pub use metadata::{Address, Kind, StorageClass};
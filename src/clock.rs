@@ -0,0 +1,102 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Sources of the current time for the checks which depend on it, such as time-locks.
+//!
+//! Passing a `SimulatedClock` instead of the `SystemClock` lets tests, here and in
+//! downstream crates, replay time-dependent scenarios deterministically.
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of the current time, in seconds since the UNIX epoch.
+pub trait Clock {
+    /// Returns the current time, in seconds since the UNIX epoch.
+    fn now(&self) -> u64;
+}
+
+/// Clock reading the system time.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    /// Returns the system time, or zero if it is set before the UNIX epoch.
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0)
+    }
+}
+
+/// Clock which only moves when told to. Clones share the same time, so a test can
+/// keep one to move the time seen by the others.
+#[derive(Clone, Default, Debug)]
+pub struct SimulatedClock {
+    now: Arc<AtomicU64>,
+}
+
+impl SimulatedClock {
+    /// Constructs a clock showing `now`, in seconds since the UNIX epoch.
+    pub fn new(now: u64) -> Self {
+        Self {
+            now: Arc::new(AtomicU64::new(now)),
+        }
+    }
+
+    /// Sets the time, which may go backwards.
+    pub fn set(&self, now: u64) {
+        self.now.store(now, Ordering::SeqCst);
+    }
+
+    /// Moves the time forward by `seconds`, stopping at `u64::MAX`.
+    pub fn advance(&self, seconds: u64) {
+        let _ = self
+            .now
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |now| {
+                Some(now.saturating_add(seconds))
+            });
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Clock, SimulatedClock, SystemClock};
+
+    #[test]
+    fn simulated_clock() {
+        let clock = SimulatedClock::new(1_000);
+        let shared = clock.clone();
+        assert_eq!(shared.now(), 1_000);
+
+        clock.advance(500);
+        assert_eq!(shared.now(), 1_500);
+        clock.set(10);
+        assert_eq!(shared.now(), 10);
+        clock.advance(u64::MAX);
+        assert_eq!(shared.now(), u64::MAX);
+
+        assert_eq!(SimulatedClock::default().now(), 0);
+    }
+
+    #[test]
+    fn system_clock() {
+        // The system time is past 2021-01-01.
+        assert!(SystemClock.now() >= 1_609_459_200);
+    }
+}
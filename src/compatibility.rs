@@ -0,0 +1,226 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Compatibility with the serialisation of previous releases.
+//!
+//! The vectors below were produced by earlier releases and must keep deserialising to the
+//! same values. A change which breaks one of them is a breaking change of the wire format
+//! and needs a bump of `PROTOCOL_VERSION`, rather than an update of the vector: the vector
+//! is then moved to `changed_layouts`, next to the version which changed it.
+
+use crate::{
+    utils, Blob, BlobAddress, DeniedAction, Error, Map, MapAction, MapAddress, MapSeqValue,
+    MapValue, PublicKey, Result, Sequence, SequenceAddress, SequenceIndex, SequencePolicy,
+    SignedResponse, SignedWrite, Token, XorName,
+};
+use serde::de::DeserializeOwned;
+use std::fmt::Debug;
+
+fn check<T: DeserializeOwned + PartialEq + Debug>(vector: &str, expected: T) -> Result<()> {
+    let bytes = hex::decode(vector).expect("invalid test vector");
+    assert_eq!(utils::deserialise::<T>(&bytes)?, expected);
+    Ok(())
+}
+
+// The Ed25519 key of the secret key `[byte; 32]`.
+fn key(byte: u8) -> PublicKey {
+    let secret = ed25519_dalek::SecretKey::from_bytes(&[byte; 32]).expect("invalid key");
+    PublicKey::Ed25519((&secret).into())
+}
+
+// Asserts that the vector no longer deserialises, its layout having changed since.
+fn check_changed<T: DeserializeOwned + Debug>(vector: &str) {
+    let bytes = hex::decode(vector).expect("invalid test vector");
+    assert!(utils::deserialise::<T>(&bytes).is_err());
+}
+
+#[test]
+fn previous_release_vectors() -> Result<()> {
+    check("002f685900000000", Token::from_nano(1_500_000_000))?;
+    check(
+        "010000000101010101010101010101010101010101010101010101010101010101010101",
+        BlobAddress::Public(XorName([1; 32])),
+    )?;
+    check(
+        "010000000202020202020202020202020202020202020202020202020202020202020202983a000000000000",
+        MapAddress::Seq {
+            name: XorName([2; 32]),
+            tag: 15000,
+        },
+    )?;
+    check(
+        "010000000303030303030303030303030303030303030303030303030303030303030303f8a7000000000000",
+        SequenceAddress::Private {
+            name: XorName([3; 32]),
+            tag: 43000,
+        },
+    )?;
+    check("010000000100000000000000", SequenceIndex::FromEnd(1))?;
+    check("04000000", Error::NoSuchEntry)?;
+    check(
+        "000000000100000000000000760200000000000000",
+        MapValue::Seq(MapSeqValue {
+            data: b"v".to_vec(),
+            version: 2,
+        }),
+    )
+}
+
+// The data in these vectors is owned by the Ed25519 key of the secret key `[1; 32]`.
+#[test]
+fn changed_layouts() {
+    // Version 2: Sequence data records whether it has been deleted.
+    check_changed::<Sequence>(
+        "0000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c\
+         0000000005000000000000006163746f72000000000404040404040404040404040404040404040404040404\
+         040404040404040404f8a700000000000000000000000000000a010000000000000005000000000000006163\
+         746f7200000000000000000000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d\
+         94121bf3748801b40f6f5c0100000000000000000000000101",
+    );
+    // Version 3: Blobs carry their storage class.
+    check_changed::<Blob>("01000000050000000000000068656c6c6f");
+    // Version 6: Sequence policies carry limits on their entries.
+    check_changed::<SequencePolicy>(
+        "000000000000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801\
+         b40f6f5c0100000000000000000000000101",
+    );
+    // Version 7: Signed writes carry an op id, after their signature. Signed writes are
+    // newer than 0.16.0, so this vector is of their first layout.
+    check_changed::<SignedWrite<Token>>(
+        "0a000000000000000000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121b\
+         f3748801b40f6f5c00000000e5422b041e57032fcdf5ec82f9df2852c35db12ffb6e43cbec683d2a3189c6f3\
+         6466bcb68f3d5e5661e240b09f2ef07d1ddde8a4d3bd792455abd8a03d9ff90d",
+    );
+    // Version 9: Maps keep the log of their permission ops.
+    check_changed::<Map>(
+        "00000000010000000202020202020202020202020202020202020202020202020202020202020202983a0000\
+         00000000010000000000000001000000000000006b0100000000000000760000000000000000000000000000\
+         000000000000000000000000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94\
+         121bf3748801b40f6f5c",
+    );
+    // Version 11: Signed responses carry the address of the data queried. Signed responses
+    // are newer than 0.16.0, so this vector is of their first layout.
+    check_changed::<SignedResponse<Token>>(
+        "0a0000000000000000000000e5422b041e57032fcdf5ec82f9df2852c35db12ffb6e43cbec683d2a3189c6f3\
+         6466bcb68f3d5e5661e240b09f2ef07d1ddde8a4d3bd792455abd8a03d9ff90d000000002000000000000000\
+         8a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c",
+    );
+}
+
+// Version 10: Access denied errors carry the action denied and the address of the data.
+#[test]
+fn changed_error_layout() -> Result<()> {
+    let vector = "\
+        000000000000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801\
+        b40f6f5c";
+    check_changed::<Error>(vector);
+
+    // The current layout, which must not change without a bump of the version.
+    let action_and_address = "\
+        0000000001000000010100000001000000020202020202020202020202020202020202020202020202020202\
+        0202020202983a000000000000";
+    check(
+        &format!("{}{}", vector, action_and_address),
+        Error::AccessDenied {
+            requester: key(1),
+            action: DeniedAction::Map(MapAction::Insert),
+            data_address: Some(
+                MapAddress::Seq {
+                    name: XorName([2; 32]),
+                    tag: 15000,
+                }
+                .into(),
+            ),
+        },
+    )
+}
+
+// Version 4: Transfers, debits and credits carry the token they are in.
+// Version 5: Transfers and debits carry the time before which they may not be registered.
+// Version 12: Signed balances and histories carry their token.
+#[cfg(feature = "transfers")]
+#[test]
+fn changed_transfer_layouts() -> Result<()> {
+    use crate::{Credit, Debit, SignedBalance, TokenId, Transfer};
+    use crdts::Dot;
+
+    // Signed balances are newer than 0.16.0, so this vector is of their first layout.
+    check_changed::<SignedBalance>(
+        "0000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c\
+         0a0000000000000003000000000000000000000000000000b2bb8b10928d014e205a1212393cdbc1c1266a73\
+         57e9d31a1c76f546b39853343596005a8e22532ffd3c0f74639f442c03eea4e1c413a21dbb70dfb1175928c1\
+         964bdfa0465854e7b389e758587d80a6993d21d8afa84a68d3554da3bb2f562d",
+    );
+
+    let transfer_vector = "\
+        0a000000000000000000000020000000000000008139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a2\
+        5df60f5b8fc9b3940000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121b\
+        f3748801b40f6f5c010000000000000003000000000000006d7367";
+    let debit_vector = "\
+        0000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c\
+        01000000000000000a00000000000000";
+    let credit_vector = "\
+        421c7c20b8226bf80daf7740e4c4afa95c16015fc0454cad913fd092a4c18aec0a0000000000000000000000\
+        20000000000000008139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b39403000000\
+        000000006d7367";
+    check_changed::<Transfer>(transfer_vector);
+    check_changed::<Debit>(debit_vector);
+    check_changed::<Credit>(credit_vector);
+
+    // With the native token and no time-lock appended, the vectors read as before.
+    let transfer = Transfer {
+        amount: Token::from_nano(10),
+        to: key(2),
+        debit_id: Dot::new(key(1), 1),
+        msg: "msg".to_string(),
+        token: TokenId::NATIVE,
+        not_before: 0,
+    };
+    let native_token = "00".repeat(32);
+    let no_time_lock = "00".repeat(8);
+    check(
+        &format!("{}{}{}", transfer_vector, native_token, no_time_lock),
+        transfer.clone(),
+    )?;
+    check(
+        &format!("{}{}{}", debit_vector, native_token, no_time_lock),
+        transfer.debit(),
+    )?;
+    check(
+        &format!("{}{}", credit_vector, native_token),
+        transfer.credit()?,
+    )
+}
+
+// Fields added with `#[serde(default)]` can be omitted by older senders using a
+// self-describing format, which is why this test needs the `cbor` feature. Under bincode
+// the same payloads fail to deserialise, as `changed_layouts` shows.
+#[cfg(feature = "cbor")]
+#[test]
+fn defaulted_fields_can_be_omitted() -> Result<()> {
+    use crate::{PublicKey, SequencePublicPolicy};
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct PreviousPublicPolicy {
+        owner: PublicKey,
+        permissions: BTreeMap<(), ()>,
+    }
+
+    let owner = PublicKey::Bls(threshold_crypto::SecretKey::random().public_key());
+    let previous = PreviousPublicPolicy {
+        owner,
+        permissions: BTreeMap::new(),
+    };
+    let policy: SequencePublicPolicy = utils::from_cbor(&utils::to_cbor(&previous)?)?;
+    assert_eq!(policy.owner, owner);
+    assert_eq!(policy.limits, Default::default());
+    Ok(())
+}
@@ -0,0 +1,88 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Contact records.
+//!
+//! Contacts are stored as entries of a Map, keyed by `Contact::entry_key` and holding the
+//! serialised `Contact`, so that any app can read the address book of another.
+
+use crate::{utils, PublicKey, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A contact of an address book.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct Contact {
+    /// Name the contact is known by.
+    pub name: String,
+    /// Key identifying the contact.
+    pub public_key: PublicKey,
+    /// Key to send tokens to, if other than `public_key`.
+    pub wallet: Option<PublicKey>,
+    /// Application-defined fields, such as an email address or a nickname.
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl Contact {
+    /// Creates a contact with no wallet key nor metadata.
+    pub fn new(name: String, public_key: PublicKey) -> Self {
+        Self {
+            name,
+            public_key,
+            wallet: None,
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the key to send tokens to, being `public_key` unless a wallet key is set.
+    pub fn wallet_key(&self) -> &PublicKey {
+        self.wallet.as_ref().unwrap_or(&self.public_key)
+    }
+
+    /// Returns the key of the contact's Map entry. Contacts are keyed by their public key,
+    /// so that renaming a contact updates its entry.
+    pub fn entry_key(&self) -> Vec<u8> {
+        self.public_key.to_bytes()
+    }
+
+    /// Serialises the contact for storing as a Map entry value.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        utils::serialise(self)
+    }
+
+    /// Deserialises a contact stored with `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        utils::deserialise(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Contact;
+    use crate::{Keypair, Result};
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn contact_round_trip() -> Result<()> {
+        let public_key = Keypair::new_ed25519(&mut OsRng).public_key();
+        let mut contact = Contact::new("alice".to_string(), public_key);
+        assert_eq!(contact.wallet_key(), &public_key);
+
+        let wallet = Keypair::new_ed25519(&mut OsRng).public_key();
+        contact.wallet = Some(wallet);
+        let _ = contact
+            .metadata
+            .insert("email".to_string(), "alice@example.com".to_string());
+        assert_eq!(contact.wallet_key(), &wallet);
+        assert_eq!(contact.entry_key(), public_key.to_bytes());
+
+        assert_eq!(Contact::from_bytes(&contact.to_bytes()?)?, contact);
+        Ok(())
+    }
+}
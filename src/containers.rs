@@ -0,0 +1,144 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Standard containers of an account.
+//!
+//! Every account owns a set of sequenced Maps, its containers, whose addresses are derived
+//! from the account's root key so the authenticator and the clients agree on them without
+//! storing the addresses anywhere. The name of a container is the SHA3-256 hash of
+//! `"container"`, the root key bytes and the container name, and its tag is
+//! `TypeTag::ACCOUNT_CONTAINER`.
+
+use crate::{Error, MapAddress, MapKind, PublicKey, Result, TypeTag};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+use xor_name::XorName;
+
+const CONTAINER_DOMAIN: &[u8] = b"container";
+
+/// A container of an account.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum Container {
+    /// The `_documents` container.
+    Documents,
+    /// The `_downloads` container.
+    Downloads,
+    /// The `_music` container.
+    Music,
+    /// The `_pictures` container.
+    Pictures,
+    /// The `_videos` container.
+    Videos,
+    /// The `_public` container.
+    Public,
+    /// The `_publicNames` container.
+    PublicNames,
+    /// The own container of an app, named after the app id.
+    App(String),
+}
+
+impl Container {
+    /// The standard containers, created with every account.
+    pub fn standard() -> Vec<Container> {
+        vec![
+            Container::Documents,
+            Container::Downloads,
+            Container::Music,
+            Container::Pictures,
+            Container::Videos,
+            Container::Public,
+            Container::PublicNames,
+        ]
+    }
+
+    /// Returns the name of the container, e.g. `_documents`, or `apps/<app id>`.
+    pub fn name(&self) -> String {
+        match self {
+            Container::Documents => "_documents".to_string(),
+            Container::Downloads => "_downloads".to_string(),
+            Container::Music => "_music".to_string(),
+            Container::Pictures => "_pictures".to_string(),
+            Container::Videos => "_videos".to_string(),
+            Container::Public => "_public".to_string(),
+            Container::PublicNames => "_publicNames".to_string(),
+            Container::App(app_id) => format!("apps/{}", app_id),
+        }
+    }
+
+    /// Returns the address of this container for the account with the given root key.
+    pub fn address(&self, root: &PublicKey) -> MapAddress {
+        let name =
+            XorName::from_content(&[CONTAINER_DOMAIN, &root.to_bytes(), self.name().as_bytes()]);
+        MapAddress::from_type_tag(MapKind::Seq, name, TypeTag::ACCOUNT_CONTAINER)
+    }
+}
+
+impl Display for Container {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.name())
+    }
+}
+
+impl FromStr for Container {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        if let Some(app_id) = name.strip_prefix("apps/") {
+            if app_id.is_empty() {
+                return Err(Error::FailedToParse("Empty app container id".to_string()));
+            }
+            return Ok(Container::App(app_id.to_string()));
+        }
+        Container::standard()
+            .into_iter()
+            .find(|container| container.name() == name)
+            .ok_or_else(|| Error::FailedToParse(format!("Unknown container {}", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Container;
+    use crate::{Keypair, Result};
+    use rand::rngs::OsRng;
+    use std::str::FromStr;
+
+    #[test]
+    fn container_names_roundtrip() -> Result<()> {
+        let mut containers = Container::standard();
+        containers.push(Container::App("net.maidsafe.app".to_string()));
+        for container in containers {
+            assert_eq!(Container::from_str(&container.name())?, container);
+        }
+        assert!(Container::from_str("_unknown").is_err());
+        assert!(Container::from_str("apps/").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn container_addresses_are_per_account() {
+        let root = Keypair::new_ed25519(&mut OsRng).public_key();
+        let other_root = Keypair::new_ed25519(&mut OsRng).public_key();
+        assert_eq!(
+            Container::Documents.address(&root),
+            Container::Documents.address(&root)
+        );
+        assert_ne!(
+            Container::Documents.address(&root),
+            Container::Public.address(&root)
+        );
+        assert_ne!(
+            Container::Documents.address(&root),
+            Container::Documents.address(&other_root)
+        );
+    }
+}
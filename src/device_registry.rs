@@ -0,0 +1,144 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{DeniedAction, Error, PublicKey, Result, SignedWrite};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A device registered with an account.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub struct Device {
+    /// Human readable name of the device.
+    pub name: String,
+    /// Time the device was added, in seconds since the UNIX epoch.
+    pub added: u64,
+}
+
+/// A change to a `DeviceRegistry`, to be signed by the account owner.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum DeviceCmd {
+    /// Registers the device key.
+    Add {
+        /// The key of the device.
+        key: PublicKey,
+        /// The device details.
+        device: Device,
+    },
+    /// Revokes the device key.
+    Remove(PublicKey),
+}
+
+/// The devices allowed to log in to an account, stored alongside its login packet.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct DeviceRegistry {
+    owner: PublicKey,
+    devices: BTreeMap<PublicKey, Device>,
+}
+
+impl DeviceRegistry {
+    /// Creates an empty registry for the account owned by `owner`.
+    pub fn new(owner: PublicKey) -> Self {
+        Self {
+            owner,
+            devices: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the account owner.
+    pub fn owner(&self) -> &PublicKey {
+        &self.owner
+    }
+
+    /// Returns the registered devices.
+    pub fn devices(&self) -> &BTreeMap<PublicKey, Device> {
+        &self.devices
+    }
+
+    /// Returns the device registered with the given key, if any.
+    pub fn get(&self, key: &PublicKey) -> Option<&Device> {
+        self.devices.get(key)
+    }
+
+    /// Returns true if the key is a registered device.
+    pub fn contains(&self, key: &PublicKey) -> bool {
+        self.devices.contains_key(key)
+    }
+
+    /// Applies a cmd signed by the account owner.
+    ///
+    /// Returns `Error::AccessDenied` if it was not signed by the owner,
+    /// `Error::InvalidOperation` when adding an already registered key and
+    /// `Error::NoSuchKey` when removing an unknown one.
+    pub fn apply(&mut self, cmd: SignedWrite<DeviceCmd>) -> Result<()> {
+        cmd.verify()?;
+        if cmd.requester != self.owner {
+            return Err(Error::access_denied(
+                cmd.requester,
+                DeniedAction::Owner,
+                None,
+            ));
+        }
+        match cmd.into_op() {
+            DeviceCmd::Add { key, device } => {
+                if self.devices.contains_key(&key) {
+                    return Err(Error::InvalidOperation);
+                }
+                let _ = self.devices.insert(key, device);
+                Ok(())
+            }
+            DeviceCmd::Remove(key) => self
+                .devices
+                .remove(&key)
+                .map(|_| ())
+                .ok_or(Error::NoSuchKey),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Device, DeviceCmd, DeviceRegistry};
+    use crate::{Error, Keypair, Result, SignedWrite};
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn add_and_remove_devices() -> Result<()> {
+        let owner = Keypair::new_ed25519(&mut OsRng);
+        let device = Keypair::new_ed25519(&mut OsRng).public_key();
+        let mut registry = DeviceRegistry::new(owner.public_key());
+
+        let add = DeviceCmd::Add {
+            key: device,
+            device: Device {
+                name: "laptop".to_string(),
+                added: 1_600_000_000,
+            },
+        };
+        registry.apply(SignedWrite::new(add.clone(), &owner)?)?;
+        assert!(registry.contains(&device));
+        assert_eq!(
+            registry.apply(SignedWrite::new(add, &owner)?),
+            Err(Error::InvalidOperation)
+        );
+
+        let other = Keypair::new_ed25519(&mut OsRng);
+        let remove = DeviceCmd::Remove(device);
+        assert!(matches!(
+            registry.apply(SignedWrite::new(remove.clone(), &other)?),
+            Err(Error::AccessDenied { .. })
+        ));
+        registry.apply(SignedWrite::new(remove.clone(), &owner)?)?;
+        assert!(!registry.contains(&device));
+        assert_eq!(
+            registry.apply(SignedWrite::new(remove, &owner)?),
+            Err(Error::NoSuchKey)
+        );
+        Ok(())
+    }
+}
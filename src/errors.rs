@@ -7,12 +7,13 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use crate::PublicKey;
+use crate::{DataAddress, MapAction, PublicKey, SequenceAction};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeMap,
     fmt::{self, Debug, Formatter},
     result,
+    time::Duration,
 };
 
 use thiserror::Error;
@@ -33,14 +34,32 @@ impl<'a, T> Debug for ErrorDebug<'a, T> {
     }
 }
 
+/// An action refused with `Error::AccessDenied`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum DeniedAction {
+    /// An action on a Map.
+    Map(MapAction),
+    /// An action on a Sequence.
+    Sequence(SequenceAction),
+    /// An action reserved to the owner, such as deleting the data.
+    Owner,
+}
+
 /// Main error type for the crate.
 #[derive(Error, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 #[non_exhaustive]
 #[allow(clippy::large_enum_variant)]
 pub enum Error {
     /// Access denied for supplied PublicKey
-    #[error("Access denied for PublicKey: {0}")]
-    AccessDenied(PublicKey),
+    #[error("Access denied for PublicKey: {requester} to perform {action:?}")]
+    AccessDenied {
+        /// The key whose request was denied.
+        requester: PublicKey,
+        /// The action which was denied.
+        action: DeniedAction,
+        /// Address of the data, if known where the check failed.
+        data_address: Option<DataAddress>,
+    },
     /// Serialization error
     #[error("Serialisation error: {0}")]
     Serialisation(String),
@@ -109,8 +128,226 @@ pub enum Error {
     /// The data for a given policy could not be located, so CRDT operations cannot be applied.
     #[error("CRDT data is in an unexpected and/or inconsistent state. No data found for current policy.")]
     CrdtUnexpectedState,
+    /// The type tag falls in the range reserved for protocol types.
+    #[error("Type tag {0} is reserved")]
+    ReservedTypeTag(u64),
+    /// A Replica event conflicts with an event already in the log.
+    #[error("Replica event conflicts with an already applied event")]
+    ConflictingReplicaEvent,
+    /// The data has been deleted, only its tombstone remains.
+    #[error("Data has been deleted")]
+    DataDeleted,
+    /// The payload was serialised with an unsupported protocol version.
+    #[error("Unsupported protocol version {0}")]
+    UnsupportedVersion(u16),
+    /// The transfer is time-locked until the given time.
+    #[error("Transfer is locked until {0}")]
+    TransferTimeLocked(u64),
+    /// An entry is larger than the data allows.
+    #[error("Entry exceeds the maximum size of {0} bytes")]
+    EntryTooLarge(u64),
+    /// The data would hold more entries than it allows.
+    #[error("Data would exceed the maximum of {0} entries")]
+    TooManyEntries(u64),
+    /// The tokens in circulation would exceed the genesis supply.
+    #[error("Tokens in circulation would exceed the genesis supply")]
+    SupplyExceeded,
+    /// Several actions were given for the same entry key.
+    #[error("Several actions for entry key {0:?}")]
+    DuplicateEntryAction(Vec<u8>),
+}
+
+impl Error {
+    /// Creates an `Error::AccessDenied`.
+    pub fn access_denied(
+        requester: PublicKey,
+        action: DeniedAction,
+        data_address: Option<DataAddress>,
+    ) -> Self {
+        Self::AccessDenied {
+            requester,
+            action,
+            data_address,
+        }
+    }
+
+    /// Returns the denied key if this is an `Error::AccessDenied`, matching the former
+    /// `AccessDenied(PublicKey)` variant.
+    pub fn denied_requester(&self) -> Option<PublicKey> {
+        match self {
+            Self::AccessDenied { requester, .. } => Some(*requester),
+            _ => None,
+        }
+    }
+
+    /// Returns a stable identifier of the kind of error, e.g. `error.access_denied`,
+    /// for looking up a localised message. Unlike the `Display` message, the identifier
+    /// never changes once released.
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            Self::AccessDenied { .. } => "error.access_denied",
+            Self::Serialisation(_) => "error.serialisation",
+            Self::EntryExists(_) => "error.entry_exists",
+            Self::InvalidEntryActions(_) => "error.invalid_entry_actions",
+            Self::NoSuchEntry => "error.no_such_entry",
+            Self::NoSuchKey => "error.no_such_key",
+            Self::InvalidOwnerNotPublicKeySet => "error.invalid_owner_not_public_key_set",
+            Self::PolicyNotSet => "error.policy_not_set",
+            Self::InvalidSuccessor(_) => "error.invalid_successor",
+            Self::OpNotCausallyReady => "error.op_not_causally_ready",
+            Self::InvalidOperation => "error.invalid_operation",
+            Self::SigningKeyTypeMismatch => "error.signing_key_type_mismatch",
+            Self::InvalidSignature => "error.invalid_signature",
+            Self::LossOfPrecision => "error.loss_of_precision",
+            Self::ExcessiveValue => "error.excessive_value",
+            Self::FailedToParse(_) => "error.failed_to_parse",
+            Self::NoSuchRecipient => "error.no_such_recipient",
+            Self::ExceededSize => "error.exceeded_size",
+            Self::CrdtMissingOpSignature => "error.crdt_missing_op_signature",
+            Self::CrdtUnexpectedState => "error.crdt_unexpected_state",
+            Self::ReservedTypeTag(_) => "error.reserved_type_tag",
+            Self::ConflictingReplicaEvent => "error.conflicting_replica_event",
+            Self::DataDeleted => "error.data_deleted",
+            Self::UnsupportedVersion(_) => "error.unsupported_version",
+            Self::TransferTimeLocked(_) => "error.transfer_time_locked",
+            Self::EntryTooLarge(_) => "error.entry_too_large",
+            Self::TooManyEntries(_) => "error.too_many_entries",
+            Self::SupplyExceeded => "error.supply_exceeded",
+            Self::DuplicateEntryAction(_) => "error.duplicate_entry_action",
+        }
+    }
+
+    /// Returns true if the request may succeed when retried unchanged, or after
+    /// re-reading the data. The classification is:
+    ///
+    /// | Error                    | Transient | Backoff |
+    /// |--------------------------|-----------|---------|
+    /// | `OpNotCausallyReady`     | yes       | 1s      |
+    /// | `CrdtUnexpectedState`    | yes       | 1s      |
+    /// | `InvalidSuccessor`       | yes       | none, re-read the data first |
+    /// | `TransferTimeLocked`     | yes       | none, retry at the time it holds |
+    /// | any other error          | no        | -       |
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::OpNotCausallyReady
+                | Self::CrdtUnexpectedState
+                | Self::InvalidSuccessor(_)
+                | Self::TransferTimeLocked(_)
+        )
+    }
+
+    /// Returns how long to wait before retrying, for the transient errors that are
+    /// resolved by the passing of time alone. See `is_transient` for the classification.
+    pub fn suggested_backoff(&self) -> Option<Duration> {
+        match self {
+            Self::OpNotCausallyReady | Self::CrdtUnexpectedState => Some(Duration::from_secs(1)),
+            _ => None,
+        }
+    }
+
+    /// Fills in the data address of an `Error::AccessDenied` if not already known.
+    /// Other errors are returned unchanged.
+    #[cfg(feature = "sequence")]
+    pub(crate) fn with_data_address(self, address: DataAddress) -> Self {
+        match self {
+            Self::AccessDenied {
+                requester,
+                action,
+                data_address: None,
+            } => Self::access_denied(requester, action, Some(address)),
+            error => error,
+        }
+    }
 }
 
 pub(crate) fn convert_bincode_error(err: bincode::Error) -> Error {
     Error::Serialisation(err.as_ref().to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{DeniedAction, Error};
+    use crate::PublicKey;
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        time::Duration,
+    };
+
+    #[test]
+    fn message_keys() {
+        let requester = PublicKey::Bls(threshold_crypto::SecretKey::random().public_key());
+        let errors = vec![
+            Error::access_denied(requester, DeniedAction::Owner, None),
+            Error::Serialisation("error".to_string()),
+            Error::EntryExists(0),
+            Error::InvalidEntryActions(BTreeMap::new()),
+            Error::NoSuchEntry,
+            Error::NoSuchKey,
+            Error::InvalidOwnerNotPublicKeySet,
+            Error::PolicyNotSet,
+            Error::InvalidSuccessor(0),
+            Error::OpNotCausallyReady,
+            Error::InvalidOperation,
+            Error::SigningKeyTypeMismatch,
+            Error::InvalidSignature,
+            Error::LossOfPrecision,
+            Error::ExcessiveValue,
+            Error::FailedToParse("error".to_string()),
+            Error::NoSuchRecipient,
+            Error::ExceededSize,
+            Error::CrdtMissingOpSignature,
+            Error::CrdtUnexpectedState,
+            Error::ReservedTypeTag(0),
+            Error::ConflictingReplicaEvent,
+            Error::DataDeleted,
+            Error::UnsupportedVersion(0),
+            Error::TransferTimeLocked(0),
+            Error::EntryTooLarge(0),
+            Error::TooManyEntries(0),
+            Error::SupplyExceeded,
+            Error::DuplicateEntryAction(vec![]),
+        ];
+        let keys: BTreeSet<_> = errors.iter().map(Error::message_key).collect();
+        assert_eq!(keys.len(), errors.len());
+        assert!(keys.iter().all(|key| key.starts_with("error.")));
+
+        // Released keys never change, whatever the error holds.
+        assert_eq!(
+            Error::access_denied(requester, DeniedAction::Owner, None).message_key(),
+            "error.access_denied"
+        );
+        assert_eq!(
+            Error::InvalidSuccessor(7).message_key(),
+            "error.invalid_successor"
+        );
+        assert_eq!(
+            Error::Serialisation("other".to_string()).message_key(),
+            "error.serialisation"
+        );
+    }
+
+    #[test]
+    fn transient_errors() {
+        let backoff = Some(Duration::from_secs(1));
+        for error in &[Error::OpNotCausallyReady, Error::CrdtUnexpectedState] {
+            assert!(error.is_transient());
+            assert_eq!(error.suggested_backoff(), backoff);
+        }
+        // Retried after re-reading the data or at the time held, rather than after a delay.
+        for error in &[Error::InvalidSuccessor(1), Error::TransferTimeLocked(1_000)] {
+            assert!(error.is_transient());
+            assert_eq!(error.suggested_backoff(), None);
+        }
+        for error in &[
+            Error::InvalidSignature,
+            Error::NoSuchEntry,
+            Error::DataDeleted,
+            Error::SupplyExceeded,
+            Error::Serialisation("error".to_string()),
+        ] {
+            assert!(!error.is_transient());
+            assert_eq!(error.suggested_backoff(), None);
+        }
+    }
+}
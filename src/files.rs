@@ -0,0 +1,153 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Files container metadata.
+//!
+//! A files container is a Map or Sequence tagged `TypeTag::FILES_CONTAINER` whose content
+//! is a serialised `FilesMap`, mapping absolute paths such as `/docs/readme.md` to the
+//! metadata of the files, which in turn link to the data holding their content.
+
+use crate::{utils, DataAddress, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Metadata of a file in a files container.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub struct FileMeta {
+    /// Size of the content in bytes.
+    pub size: u64,
+    /// MIME type of the content, e.g. `text/plain`.
+    pub content_type: String,
+    /// Time the file was created, in seconds since the UNIX epoch.
+    pub created: u64,
+    /// Time the file was last modified, in seconds since the UNIX epoch.
+    pub modified: u64,
+    /// Address of the data holding the content.
+    pub link: DataAddress,
+}
+
+/// The files of a files container, by absolute path.
+#[derive(Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct FilesMap {
+    files: BTreeMap<String, FileMeta>,
+}
+
+impl FilesMap {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the metadata of the file at `path`, if any.
+    pub fn get(&self, path: &str) -> Option<&FileMeta> {
+        self.files.get(path)
+    }
+
+    /// Sets the metadata of the file at `path`, returning the previous one.
+    /// Returns `Error::FailedToParse` if the path is not a valid absolute file path.
+    pub fn insert(&mut self, path: &str, meta: FileMeta) -> Result<Option<FileMeta>> {
+        validate_path(path)?;
+        Ok(self.files.insert(path.to_string(), meta))
+    }
+
+    /// Removes the file at `path`, returning its metadata.
+    pub fn remove(&mut self, path: &str) -> Option<FileMeta> {
+        self.files.remove(path)
+    }
+
+    /// Returns all the files, ordered by path.
+    pub fn files(&self) -> impl Iterator<Item = (&str, &FileMeta)> {
+        self.files.iter().map(|(path, meta)| (path.as_str(), meta))
+    }
+
+    /// Returns the files under the directory `dir`, at any depth, ordered by path.
+    pub fn list<'a>(&'a self, dir: &str) -> impl Iterator<Item = (&'a str, &'a FileMeta)> {
+        let prefix = if dir.ends_with('/') {
+            dir.to_string()
+        } else {
+            format!("{}/", dir)
+        };
+        self.files
+            .range(prefix.clone()..)
+            .take_while(move |(path, _)| path.starts_with(&prefix))
+            .map(|(path, meta)| (path.as_str(), meta))
+    }
+
+    /// Returns the number of files.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns true if there are no files.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Serialises the map, to be stored as the content of its container.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        utils::serialise(self)
+    }
+
+    /// Deserialises a map stored with `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        utils::deserialise(bytes)
+    }
+}
+
+// A valid path is absolute, doesn't end with a separator, and has no empty segments.
+fn validate_path(path: &str) -> Result<()> {
+    let valid = path.starts_with('/')
+        && path.len() > 1
+        && path[1..].split('/').all(|segment| !segment.is_empty());
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::FailedToParse(format!(
+            "Invalid file path {:?}",
+            path
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FileMeta, FilesMap};
+    use crate::{BlobAddress, Result};
+    use xor_name::XorName;
+
+    fn file(size: u64) -> FileMeta {
+        FileMeta {
+            size,
+            content_type: "text/plain".to_string(),
+            created: 1_600_000_000,
+            modified: 1_600_000_000,
+            link: BlobAddress::Public(XorName::random()).into(),
+        }
+    }
+
+    #[test]
+    fn path_accessors() -> Result<()> {
+        let mut files = FilesMap::new();
+        let _ = files.insert("/readme.md", file(1))?;
+        let _ = files.insert("/docs/a.md", file(2))?;
+        let _ = files.insert("/docs/nested/b.md", file(3))?;
+        let _ = files.insert("/docsx.md", file(4))?;
+        assert!(files.insert("docs/c.md", file(5)).is_err());
+        assert!(files.insert("/docs//c.md", file(5)).is_err());
+        assert!(files.insert("/docs/", file(5)).is_err());
+
+        let listed: Vec<_> = files.list("/docs").map(|(path, _)| path).collect();
+        assert_eq!(listed, vec!["/docs/a.md", "/docs/nested/b.md"]);
+        assert_eq!(files.list("/").count(), 4);
+
+        let files = FilesMap::from_bytes(&files.to_bytes()?)?;
+        assert_eq!(files.get("/docs/a.md").map(|meta| meta.size), Some(2));
+        Ok(())
+    }
+}
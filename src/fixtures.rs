@@ -0,0 +1,93 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Deterministic constructors for use in downstream tests, enabled by the `fixtures` feature.
+//!
+//! Every function derives its output from the given seed alone, so the same seed produces the
+//! same data in every crate and on every run.
+
+use crate::{
+    blob::{self, PublicData as PublicBlob},
+    keys::{Keypair, PublicKey},
+    map::{self, SeqData as SeqMap, SeqValue},
+    sequence::Data as Sequence,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use xor_name::XorName;
+
+/// Type tag used by the Map and Sequence fixtures.
+pub const TEST_TYPE_TAG: u64 = 15_000;
+
+/// Returns the Ed25519 keypair derived from `seed`.
+pub fn test_keypair(seed: u64) -> Keypair {
+    let mut rng = StdRng::seed_from_u64(seed);
+    Keypair::new_ed25519(&mut rng)
+}
+
+/// Returns the public key of `test_keypair(seed)`.
+pub fn test_public_key(seed: u64) -> PublicKey {
+    test_keypair(seed).public_key()
+}
+
+/// Returns the `XorName` derived from `seed`.
+pub fn test_xor_name(seed: u64) -> XorName {
+    XorName::from_content(&[b"fixture", &seed.to_be_bytes()])
+}
+
+/// Returns a public Blob whose content is derived from `seed`.
+pub fn test_blob(seed: u64) -> blob::Data {
+    PublicBlob::new(test_xor_name(seed).0.to_vec()).into()
+}
+
+/// Returns a sequenced Map named after `seed`, owned by `test_public_key(seed)` and holding
+/// a single entry.
+pub fn test_map(seed: u64) -> map::Data {
+    let mut entries = map::SeqEntries::new();
+    let _ = entries.insert(
+        b"key".to_vec(),
+        SeqValue {
+            data: seed.to_be_bytes().to_vec(),
+            version: 0,
+        },
+    );
+    SeqMap::new_with_data(
+        test_xor_name(seed),
+        TEST_TYPE_TAG,
+        entries,
+        Default::default(),
+        test_public_key(seed),
+    )
+    .into()
+}
+
+/// Returns an empty public Sequence named after `seed`, owned by `test_public_key(seed)`.
+pub fn test_sequence(seed: u64) -> Sequence {
+    let owner = test_public_key(seed);
+    Sequence::new_public(
+        owner,
+        owner.to_string(),
+        test_xor_name(seed),
+        TEST_TYPE_TAG,
+        None,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixtures_are_deterministic() {
+        assert_eq!(test_public_key(7), test_public_key(7));
+        assert_ne!(test_public_key(7), test_public_key(8));
+        assert_eq!(test_blob(7), test_blob(7));
+        assert_eq!(test_map(7), test_map(7));
+        assert_eq!(test_sequence(7).address(), test_sequence(7).address());
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{Error, Result, Signature, SignatureShare};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use threshold_crypto::PublicKeySet;
+use tiny_keccak::{Hasher, Sha3};
+
+/// The quorum rules of a group of BLS key holders, such as transfer Replicas or
+/// section Elders, i.e. how many signature shares are needed for an agreement.
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Debug)]
+pub struct QuorumConfig {
+    threshold: usize,
+}
+
+impl QuorumConfig {
+    /// Constructs the quorum rules of a group with the given BLS threshold.
+    pub fn new(threshold: usize) -> Self {
+        Self { threshold }
+    }
+
+    /// The BLS threshold of the group.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Number of signature shares needed to reach agreement, i.e. `threshold + 1`.
+    pub fn required_shares(&self) -> usize {
+        self.threshold + 1
+    }
+
+    /// Returns `true` if the number of shares is enough to reach agreement.
+    pub fn is_reached(&self, share_count: usize) -> bool {
+        share_count >= self.required_shares()
+    }
+}
+
+impl From<&PublicKeySet> for QuorumConfig {
+    fn from(public_key_set: &PublicKeySet) -> Self {
+        Self::new(public_key_set.threshold())
+    }
+}
+
+/// Collects the BLS signature shares of the members of a section over the same content,
+/// such as the same event sent by each Elder, and combines them into the section
+/// signature once the quorum of the key set is reached.
+///
+/// Shares are grouped by an id chosen by the caller, e.g. a message id, and by the hash
+/// of the signed content, so shares over different content under the same id never combine.
+#[derive(Clone, Debug)]
+pub struct Accumulation<K> {
+    public_key_set: PublicKeySet,
+    pending: BTreeMap<(K, [u8; 32]), BTreeMap<usize, threshold_crypto::SignatureShare>>,
+}
+
+impl<K: Ord + Clone> Accumulation<K> {
+    /// Creates an accumulation of shares of the given section key set.
+    pub fn new(public_key_set: PublicKeySet) -> Self {
+        Self {
+            public_key_set,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a share over `content`, returning the section signature once enough shares
+    /// are collected for the id and content, which are then dropped.
+    /// Returns `Err(InvalidSignature)` if the share does not verify.
+    pub fn add(
+        &mut self,
+        id: K,
+        content: &[u8],
+        share: SignatureShare,
+    ) -> Result<Option<Signature>> {
+        let key_share = self.public_key_set.public_key_share(share.index);
+        if !key_share.verify(&share.share, content) {
+            return Err(Error::InvalidSignature);
+        }
+
+        let key = (id, content_hash(content));
+        let shares = self.pending.entry(key.clone()).or_default();
+        let _ = shares.insert(share.index, share.share);
+        if !QuorumConfig::from(&self.public_key_set).is_reached(shares.len()) {
+            return Ok(None);
+        }
+        let signature = self
+            .public_key_set
+            .combine_signatures(shares.iter().map(|(index, share)| (*index, share)))
+            .map_err(|_| Error::InvalidSignature)?;
+        let _ = self.pending.remove(&key);
+        Ok(Some(Signature::Bls(signature)))
+    }
+
+    /// Drops the shares collected under `id`, e.g. once it has expired.
+    pub fn remove(&mut self, id: &K) {
+        self.pending.retain(|(pending_id, _), _| pending_id != id);
+    }
+
+    /// Returns the number of id and content pairs with shares still pending.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+fn content_hash(content: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha3::v256();
+    let mut hash = [0; 32];
+    hasher.update(content);
+    hasher.finalize(&mut hash);
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Accumulation, QuorumConfig};
+    use crate::{PublicKey, Result, SignatureShare};
+    use rand::rngs::OsRng;
+    use threshold_crypto::SecretKeySet;
+
+    #[test]
+    fn quorum_config() {
+        let quorum = QuorumConfig::from(&SecretKeySet::random(2, &mut OsRng).public_keys());
+        assert_eq!(quorum.threshold(), 2);
+        assert_eq!(quorum.required_shares(), 3);
+        assert!(!quorum.is_reached(2));
+        assert!(quorum.is_reached(3));
+    }
+
+    #[test]
+    fn combines_above_threshold() -> Result<()> {
+        let section = SecretKeySet::random(1, &mut OsRng);
+        let mut accumulation = Accumulation::new(section.public_keys());
+        let share = |index: usize, content: &[u8]| SignatureShare {
+            index,
+            share: section.secret_key_share(index).sign(content),
+        };
+
+        assert_eq!(accumulation.add(1, b"event", share(0, b"event"))?, None);
+        assert_eq!(accumulation.add(1, b"other", share(1, b"other"))?, None);
+        assert_eq!(accumulation.add(1, b"event", share(0, b"event"))?, None);
+        assert!(accumulation.add(1, b"event", share(2, b"other")).is_err());
+        assert_eq!(accumulation.pending(), 2);
+
+        let signature = accumulation
+            .add(1, b"event", share(2, b"event"))?
+            .expect("threshold reached");
+        PublicKey::Bls(section.public_keys().public_key()).verify(&signature, b"event")?;
+        assert_eq!(accumulation.pending(), 1);
+
+        accumulation.remove(&1);
+        assert_eq!(accumulation.pending(), 0);
+        Ok(())
+    }
+}
@@ -21,7 +21,9 @@ use rand::{CryptoRng, Rng};
 use serde::{Deserialize, Serialize};
 use std::fmt::{self, Debug, Formatter};
 use std::sync::Arc;
+use subtle::ConstantTimeEq;
 use threshold_crypto::{self, serde_impl::SerdeSecret, PublicKeySet};
+use zeroize::Zeroizing;
 
 #[derive(Clone, PartialEq, Eq)]
 /// Entity that owns the data or tokens.
@@ -135,8 +137,9 @@ impl PartialEq for Keypair {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::Ed25519(keypair), Self::Ed25519(other_keypair)) => {
-                // TODO: After const generics land, remove the `to_vec()` calls.
-                keypair.to_bytes().to_vec() == other_keypair.to_bytes().to_vec()
+                let bytes = Zeroizing::new(keypair.to_bytes());
+                let other_bytes = Zeroizing::new(other_keypair.to_bytes());
+                bytes[..].ct_eq(&other_bytes[..]).into()
             }
             (Self::BlsShare(keypair), Self::BlsShare(other_keypair)) => keypair == other_keypair,
             _ => false,
@@ -180,8 +183,8 @@ impl Keypair {
     pub fn secret_key(&self) -> Result<SecretKey> {
         match self {
             Self::Ed25519(keypair) => {
-                let bytes = keypair.secret.to_bytes();
-                match ed25519_dalek::SecretKey::from_bytes(&bytes) {
+                let bytes = Zeroizing::new(keypair.secret.to_bytes());
+                match ed25519_dalek::SecretKey::from_bytes(&bytes[..]) {
                     Ok(sk) => Ok(SecretKey::Ed25519(sk)),
                     Err(_) => Err(Error::FailedToParse(
                         "Could not deserialise Ed25519 secret key".to_string(),
@@ -273,4 +276,20 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn secret_key_equality() -> Result<()> {
+        let keypairs = gen_keypairs();
+
+        for keypair in &keypairs {
+            assert_eq!(keypair.secret_key()?, keypair.secret_key()?);
+        }
+        assert_ne!(keypairs[0].secret_key()?, keypairs[1].secret_key()?);
+        assert_ne!(
+            keypairs[0].secret_key()?,
+            Keypair::new_ed25519(&mut rand::thread_rng()).secret_key()?
+        );
+
+        Ok(())
+    }
 }
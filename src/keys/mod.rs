@@ -13,14 +13,18 @@
 //! `new` functions. A `PublicKey` can't be generated by itself; it must always be derived from a
 //! secret key.
 
+mod accumulation;
 mod keypair;
 mod node_keypairs;
+mod public_id;
 mod public_key;
 mod secret_key;
 mod signature;
 
 pub use self::signature::*;
+pub use accumulation::*;
 pub use keypair::*;
 pub use node_keypairs::*;
+pub use public_id::*;
 pub use public_key::*;
 pub use secret_key::*;
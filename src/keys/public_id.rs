@@ -0,0 +1,158 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{Keypair, PublicKey, Signature};
+use crate::{utils, Result};
+use serde::{Deserialize, Serialize};
+use xor_name::XorName;
+
+/// Public identity of a client, i.e. of the owner of an account.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub struct ClientPublicId {
+    public_key: PublicKey,
+}
+
+impl ClientPublicId {
+    /// Constructs the identity of the client with the given key.
+    pub fn new(public_key: PublicKey) -> Self {
+        Self { public_key }
+    }
+
+    /// Returns the client's key.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// Returns the client's name, derived from its key.
+    pub fn name(&self) -> XorName {
+        self.public_key.into()
+    }
+}
+
+/// Public identity of an app acting on behalf of a client, carrying the client's
+/// endorsement of the app key.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct AppPublicId {
+    app_key: PublicKey,
+    owner: ClientPublicId,
+    endorsement: Signature,
+}
+
+impl AppPublicId {
+    /// Endorses `app_key` with the owner's keypair.
+    pub fn new(app_key: PublicKey, owner: &Keypair) -> Result<Self> {
+        let endorsement = owner.sign(&utils::serialise(&app_key)?);
+        Ok(Self {
+            app_key,
+            owner: ClientPublicId::new(owner.public_key()),
+            endorsement,
+        })
+    }
+
+    /// Returns the app's key.
+    pub fn app_key(&self) -> &PublicKey {
+        &self.app_key
+    }
+
+    /// Returns the identity of the client owning the app.
+    pub fn owner(&self) -> &ClientPublicId {
+        &self.owner
+    }
+
+    /// Returns the owner's signature over the app key.
+    pub fn endorsement(&self) -> &Signature {
+        &self.endorsement
+    }
+
+    /// Returns `Ok(())` if the app key is endorsed by the owner,
+    /// and `Err(InvalidSignature)` otherwise.
+    pub fn verify(&self) -> Result<()> {
+        self.owner
+            .public_key()
+            .verify(&self.endorsement, &utils::serialise(&self.app_key)?)
+    }
+}
+
+/// Public identity of the author of a request.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub enum PublicId {
+    /// A client signing with its own key.
+    Client(ClientPublicId),
+    /// An app signing with a key endorsed by its owner. Boxed, as it is several times
+    /// the size of a client identity.
+    App(Box<AppPublicId>),
+}
+
+impl PublicId {
+    /// Returns the key requests are signed with.
+    pub fn public_key(&self) -> &PublicKey {
+        match self {
+            Self::Client(id) => id.public_key(),
+            Self::App(id) => id.app_key(),
+        }
+    }
+
+    /// Returns the identity of the client the requests are made on behalf of.
+    pub fn owner(&self) -> &ClientPublicId {
+        match self {
+            Self::Client(id) => id,
+            Self::App(id) => id.owner(),
+        }
+    }
+
+    /// Verifies that a request signed with `signature` over `data` was authorised
+    /// by the owner, checking the app endorsement if any.
+    pub fn verify(&self, signature: &Signature, data: &[u8]) -> Result<()> {
+        if let Self::App(id) = self {
+            id.verify()?;
+        }
+        self.public_key().verify(signature, data)
+    }
+}
+
+impl From<ClientPublicId> for PublicId {
+    fn from(id: ClientPublicId) -> Self {
+        Self::Client(id)
+    }
+}
+
+impl From<AppPublicId> for PublicId {
+    fn from(id: AppPublicId) -> Self {
+        Self::App(Box::new(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AppPublicId, PublicId};
+    use crate::{Error, Keypair, Result};
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn app_endorsement() -> Result<()> {
+        let owner = Keypair::new_ed25519(&mut OsRng);
+        let app = Keypair::new_ed25519(&mut OsRng);
+        let app_id = AppPublicId::new(app.public_key(), &owner)?;
+        app_id.verify()?;
+
+        let id = PublicId::from(app_id.clone());
+        assert_eq!(id.owner().public_key(), &owner.public_key());
+        id.verify(&app.sign(b"request"), b"request")?;
+
+        let forged = AppPublicId {
+            endorsement: app.sign(b"not the app key"),
+            ..app_id
+        };
+        assert_eq!(
+            PublicId::from(forged).verify(&app.sign(b"request"), b"request"),
+            Err(Error::InvalidSignature)
+        );
+        Ok(())
+    }
+}
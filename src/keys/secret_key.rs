@@ -13,11 +13,16 @@
 //! `new` functions. A `PublicKey` can't be generated by itself; it must always be derived from a
 //! secret key.
 
+use crate::utils;
 use serde::{Deserialize, Serialize};
 use std::fmt::{self, Debug, Display, Formatter};
+use subtle::{Choice, ConstantTimeEq};
 use threshold_crypto::{self, serde_impl::SerdeSecret};
+use zeroize::Zeroizing;
 // TODO: remove clones. We need to restructure to hold keypair ones and only require references for this.
 /// Wrapper for different secret key types.
+///
+/// Both underlying key types zeroize their memory on drop.
 #[derive(Debug, Serialize, Deserialize)]
 pub enum SecretKey {
     /// Ed25519 secretkey.
@@ -31,3 +36,32 @@ impl Display for SecretKey {
         Debug::fmt(self, formatter)
     }
 }
+
+impl ConstantTimeEq for SecretKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        match (self, other) {
+            (Self::Ed25519(key), Self::Ed25519(other_key)) => {
+                key.as_bytes().ct_eq(other_key.as_bytes())
+            }
+            (Self::BlsShare(share), Self::BlsShare(other_share)) => {
+                match (utils::serialise(share), utils::serialise(other_share)) {
+                    (Ok(bytes), Ok(other_bytes)) => {
+                        let bytes = Zeroizing::new(bytes);
+                        let other_bytes = Zeroizing::new(other_bytes);
+                        bytes.as_slice().ct_eq(other_bytes.as_slice())
+                    }
+                    _ => Choice::from(0),
+                }
+            }
+            _ => Choice::from(0),
+        }
+    }
+}
+
+impl PartialEq for SecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for SecretKey {}
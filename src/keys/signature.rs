@@ -20,6 +20,7 @@ use std::{
     fmt::{self, Debug, Formatter},
     hash::{Hash, Hasher},
 };
+use subtle::{Choice, ConstantTimeEq};
 
 /// A signature share, with its index in the combined collection.
 #[derive(Clone, Hash, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Debug)]
@@ -31,7 +32,7 @@ pub struct SignatureShare {
 }
 
 /// Wrapper for different signature types.
-#[derive(Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 #[allow(clippy::large_enum_variant)]
 pub enum Signature {
     /// Ed25519 signature.
@@ -85,7 +86,25 @@ impl From<(usize, threshold_crypto::SignatureShare)> for Signature {
     }
 }
 
-#[allow(clippy::derive_hash_xor_eq)]
+impl ConstantTimeEq for Signature {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        match (utils::serialise(self), utils::serialise(other)) {
+            (Ok(bytes), Ok(other_bytes)) => bytes.as_slice().ct_eq(other_bytes.as_slice()),
+            _ => Choice::from(0),
+        }
+    }
+}
+
+// Compares in constant time, so that comparing against a forged signature
+// does not leak how much of it is correct.
+impl PartialEq for Signature {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for Signature {}
+
 impl Hash for Signature {
     fn hash<H: Hasher>(&self, state: &mut H) {
         utils::serialise(&self).hash(state)
@@ -8,6 +8,28 @@
 // Software.
 
 //! SAFE network data types.
+//!
+//! ## Compatibility
+//!
+//! Fields added to existing types go after the existing ones and are marked
+//! `#[serde(default)]`, so that messages from older senders still deserialise in
+//! self-describing formats such as CBOR. This gives no compatibility at all with bincode:
+//! bincode is not self-describing, and a bincode payload lacking a field fails to
+//! deserialise whether or not the field has a default. Any change to the bincode layout
+//! of a type therefore needs a bump of `PROTOCOL_VERSION`. The `compatibility` tests hold
+//! vectors from previous releases, both those which must keep deserialising unchanged and
+//! those whose layout has since changed.
+//!
+//! ## Features
+//!
+//! Each data type is behind a feature, all on by default, so that clients can leave out
+//! those they do not handle: `blob`, `map`, `sequence`, `transfers`, and `account` for the
+//! account-level types (contacts, containers, device registries, files and NRS maps).
+//!
+//! The addresses, kinds and actions of Blobs, Maps and Sequences are kept either way, as
+//! are the `Error` variants of every type, so that `DataAddress`, `DeniedAction` and `Error`
+//! deserialise the same in every build. `Data`, which holds any of the data types, is only
+//! built with all of `blob`, `map` and `sequence`.
 
 #![doc(
     html_logo_url = "https://raw.githubusercontent.com/maidsafe/QA/master/Images/maidsafe_logo.png",
@@ -29,50 +51,133 @@
 )]
 
 mod blob;
+mod clock;
+#[cfg(all(test, feature = "blob", feature = "map", feature = "sequence"))]
+mod compatibility;
+#[cfg(feature = "account")]
+mod contact;
+#[cfg(feature = "account")]
+mod containers;
+#[cfg(feature = "account")]
+mod device_registry;
 mod errors;
+#[cfg(feature = "account")]
+mod files;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
 mod keys;
 mod map;
+#[cfg(feature = "account")]
+mod nrs;
 mod sequence;
+mod signed_response;
+mod signed_write;
 mod token;
+#[cfg(feature = "transfers")]
 mod transfer;
+mod type_tag;
 mod utils;
+mod version;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
+pub use blob::{Address as BlobAddress, Kind as BlobKind, StorageClass as BlobStorageClass};
+#[cfg(feature = "blob")]
 pub use blob::{
-    Address as BlobAddress, Data as Blob, Kind as BlobKind, PrivateData as PrivateBlob,
-    PublicData as PublicBlob, MAX_BLOB_SIZE_IN_BYTES,
+    ChallengeNonce, ChunkProof, Data as Blob, PrivateData as PrivateBlob, PublicData as PublicBlob,
+    MAX_BLOB_SIZE_IN_BYTES,
 };
-pub use errors::{Error, Result};
+pub use clock::{Clock, SimulatedClock, SystemClock};
+#[cfg(feature = "account")]
+pub use contact::Contact;
+#[cfg(feature = "account")]
+pub use containers::Container;
+#[cfg(feature = "account")]
+pub use device_registry::{Device, DeviceCmd, DeviceRegistry};
+pub use errors::{DeniedAction, Error, Result};
+#[cfg(feature = "account")]
+pub use files::{FileMeta, FilesMap};
 
 pub use keys::{
-    BlsKeypairShare, Keypair, NodeKeypairs, OwnerType, PublicKey, SecretKey, Signature,
-    SignatureShare, Signing,
+    Accumulation, AppPublicId, BlsKeypairShare, ClientPublicId, Keypair, NodeKeypairs, OwnerType,
+    PublicId, PublicKey, QuorumConfig, SecretKey, Signature, SignatureShare, Signing,
 };
+pub use map::{Action as MapAction, Address as MapAddress, Kind as MapKind};
+#[cfg(feature = "map")]
 pub use map::{
-    Action as MapAction, Address as MapAddress, Data as Map, Entries as MapEntries,
-    EntryActions as MapEntryActions, Kind as MapKind, PermissionSet as MapPermissionSet,
-    SeqData as SeqMap, SeqEntries as MapSeqEntries, SeqEntryAction as MapSeqEntryAction,
-    SeqEntryActions as MapSeqEntryActions, SeqValue as MapSeqValue, UnseqData as UnseqMap,
-    UnseqEntries as MapUnseqEntries, UnseqEntryAction as MapUnseqEntryAction,
-    UnseqEntryActions as MapUnseqEntryActions, Value as MapValue, Values as MapValues,
+    Data as Map, Entries as MapEntries, EntryActions as MapEntryActions,
+    EntryActionsBuilder as MapEntryActionsBuilder, PermissionChange as MapPermissionChange,
+    PermissionDelta as MapPermissionDelta, PermissionOp as MapPermissionOp,
+    PermissionSet as MapPermissionSet, SeqData as SeqMap, SeqEntries as MapSeqEntries,
+    SeqEntryAction as MapSeqEntryAction, SeqEntryActions as MapSeqEntryActions,
+    SeqValue as MapSeqValue, UnseqData as UnseqMap, UnseqEntries as MapUnseqEntries,
+    UnseqEntryAction as MapUnseqEntryAction, UnseqEntryActions as MapUnseqEntryActions,
+    Value as MapValue, ValueHistory as MapValueHistory, ValueHistoryEntry as MapValueHistoryEntry,
+    Values as MapValues, MAX_MAP_SIZE_IN_BYTES,
 };
+#[cfg(feature = "account")]
+pub use nrs::{NrsMap, NrsRecord, SubName};
 
-pub use token::Token;
+pub use signed_response::SignedResponse;
+pub use signed_write::{AppliedOps, OpId, SignedWrite};
+pub use token::{RoundingMode, Token, TokenFormatter, TokenId, TokenUnit, GENESIS_SUPPLY};
 
 pub use sequence::{
-    Action as SequenceAction, Address as SequenceAddress, Data as Sequence, DataOp as SequenceOp,
-    Entries as SequenceEntries, Entry as SequenceEntry, Index as SequenceIndex,
-    Kind as SequenceKind, Permissions as SequencePermissions, Policy as SequencePolicy,
+    Action as SequenceAction, Address as SequenceAddress, Entries as SequenceEntries,
+    Entry as SequenceEntry, Index as SequenceIndex, Kind as SequenceKind, Limits as SequenceLimits,
+    Permissions as SequencePermissions, Policy as SequencePolicy,
     PrivatePermissions as SequencePrivatePermissions, PrivatePolicy as SequencePrivatePolicy,
-    PrivateSeqData, PublicPermissions as SequencePublicPermissions,
-    PublicPolicy as SequencePublicPolicy, PublicSeqData, User as SequenceUser,
+    PublicPermissions as SequencePublicPermissions, PublicPolicy as SequencePublicPolicy,
+    SignedPolicy as SequenceSignedPolicy, User as SequenceUser,
 };
+#[cfg(feature = "sequence")]
+pub use sequence::{
+    Data as Sequence, DataBatchOp as SequenceBatchOp, DataOp as SequenceOp,
+    EntryHash as SequenceEntryHash, EntryProof as SequenceEntryProof,
+    MetadataOp as SequenceMetadataOp, PrivateSeqData, PublicSeqData,
+    MAX_SEQUENCE_METADATA_ENTRY_SIZE,
+};
+#[cfg(feature = "transfers")]
 pub use transfer::*;
+pub use type_tag::TypeTag;
+pub use utils::derive_xor_name;
+#[cfg(feature = "cbor")]
+pub use utils::{from_cbor, to_cbor};
+#[cfg(feature = "msgpack")]
+pub use utils::{from_msgpack, to_msgpack};
+pub use version::{
+    deserialise_versioned, is_compatible, negotiate_version, payload_version, serialise_versioned,
+    MIN_SUPPORTED_VERSION, PROTOCOL_VERSION,
+};
+pub use xor_name::{XorName, XOR_NAME_LEN};
 
 use serde::{Deserialize, Serialize};
-use std::fmt::Debug;
-use xor_name::XorName;
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// Scope of a piece of data, common to all data types.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum Scope {
+    /// Public data, which can be read by anyone.
+    Public,
+    /// Private data, which can only be read by its owner and the users it grants access to.
+    Private,
+}
+
+impl Scope {
+    /// Returns true if public.
+    pub fn is_public(self) -> bool {
+        self == Scope::Public
+    }
 
-/// Object storing a data variant.
+    /// Returns true if private.
+    pub fn is_private(self) -> bool {
+        !self.is_public()
+    }
+}
+
+/// Object storing a data variant. It holds any of the data types, so it is built only
+/// with all of them.
+#[cfg(all(feature = "blob", feature = "map", feature = "sequence"))]
 #[allow(clippy::large_enum_variant)]
 #[derive(Clone, Eq, PartialEq, PartialOrd, Hash, Serialize, Deserialize, Debug)]
 pub enum Data {
@@ -84,36 +189,259 @@ pub enum Data {
     Sequence(Sequence),
 }
 
+#[cfg(all(feature = "blob", feature = "map", feature = "sequence"))]
 impl Data {
-    /// Returns true if public.
-    pub fn is_public(&self) -> bool {
+    /// Returns the scope.
+    pub fn scope(&self) -> Scope {
         match *self {
-            Self::Immutable(ref idata) => idata.is_public(),
-            Self::Mutable(_) => false,
-            Self::Sequence(ref sequence) => sequence.is_public(),
+            Self::Immutable(ref idata) => idata.scope(),
+            Self::Mutable(ref map) => map.scope(),
+            Self::Sequence(ref sequence) => sequence.scope(),
         }
     }
 
+    /// Returns the address.
+    pub fn address(&self) -> DataAddress {
+        match *self {
+            Self::Immutable(ref idata) => (*idata.address()).into(),
+            Self::Mutable(ref map) => (*map.address()).into(),
+            Self::Sequence(ref sequence) => (*sequence.address()).into(),
+        }
+    }
+
+    /// Returns true if public.
+    pub fn is_public(&self) -> bool {
+        self.scope().is_public()
+    }
+
     /// Returns true if private.
     pub fn is_private(&self) -> bool {
         !self.is_public()
     }
+
+    /// Returns the metadata of the data, or an error if the entries of a Sequence
+    /// cannot be counted, e.g. once it is deleted, or the data cannot be serialised.
+    pub fn metadata(&self) -> Result<DataMetadata> {
+        let (owner, version, size) = match *self {
+            Self::Immutable(ref idata) => (idata.owner().copied(), None, idata.serialised_size()),
+            Self::Mutable(ref map) => (
+                Some(map.owner()),
+                Some(map.version()),
+                map.serialised_size(),
+            ),
+            Self::Sequence(ref sequence) => (
+                Some(sequence.owner()),
+                Some(sequence.len(None)?),
+                bincode::serialized_size(sequence).map_err(errors::convert_bincode_error)?,
+            ),
+        };
+        let storage_class = match *self {
+            Self::Immutable(ref idata) => Some(idata.storage_class()),
+            _ => None,
+        };
+        Ok(DataMetadata {
+            address: self.address(),
+            owner,
+            version,
+            size,
+            created: None,
+            storage_class,
+        })
+    }
+}
+
+/// Address of a data variant.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum DataAddress {
+    /// Blob address.
+    Blob(BlobAddress),
+    /// Map address.
+    Map(MapAddress),
+    /// Sequence address.
+    Sequence(SequenceAddress),
 }
 
+impl DataAddress {
+    /// Returns the name.
+    pub fn name(&self) -> &XorName {
+        match self {
+            Self::Blob(address) => address.name(),
+            Self::Map(address) => address.name(),
+            Self::Sequence(address) => address.name(),
+        }
+    }
+
+    /// Returns the scope.
+    pub fn scope(&self) -> Scope {
+        match self {
+            Self::Blob(address) => address.scope(),
+            Self::Map(address) => address.scope(),
+            Self::Sequence(address) => address.scope(),
+        }
+    }
+}
+
+impl Display for DataAddress {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Blob(address) => Display::fmt(address, formatter),
+            Self::Map(address) => Display::fmt(address, formatter),
+            Self::Sequence(address) => Display::fmt(address, formatter),
+        }
+    }
+}
+
+impl From<BlobAddress> for DataAddress {
+    fn from(address: BlobAddress) -> Self {
+        Self::Blob(address)
+    }
+}
+
+impl From<MapAddress> for DataAddress {
+    fn from(address: MapAddress) -> Self {
+        Self::Map(address)
+    }
+}
+
+impl From<SequenceAddress> for DataAddress {
+    fn from(address: SequenceAddress) -> Self {
+        Self::Sequence(address)
+    }
+}
+
+/// Ownership and other metadata of a piece of data, for displaying it without
+/// fetching its content.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct DataMetadata {
+    /// Address of the data, giving its kind and name.
+    pub address: DataAddress,
+    /// Owner of the data, if any. Public Blobs have no owner.
+    pub owner: Option<PublicKey>,
+    /// Version of the data: the version of a Map or the number of entries of a Sequence.
+    /// Blobs are never modified and have no version.
+    pub version: Option<u64>,
+    /// Size of the data after serialisation.
+    pub size: u64,
+    /// Time the data was stored, in seconds since the UNIX epoch, if recorded.
+    /// The data types do not record it, so `Data::metadata` always leaves it `None`
+    /// for the storing node to fill in.
+    pub created: Option<u64>,
+    /// Storage class of the data. Only Blobs have one.
+    pub storage_class: Option<BlobStorageClass>,
+}
+
+#[cfg(all(feature = "blob", feature = "map", feature = "sequence"))]
 impl From<Blob> for Data {
     fn from(data: Blob) -> Self {
         Self::Immutable(data)
     }
 }
 
+#[cfg(all(feature = "blob", feature = "map", feature = "sequence"))]
 impl From<Map> for Data {
     fn from(data: Map) -> Self {
         Self::Mutable(data)
     }
 }
 
+#[cfg(all(feature = "blob", feature = "map", feature = "sequence"))]
 impl From<Sequence> for Data {
     fn from(data: Sequence) -> Self {
         Self::Sequence(data)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{BlobAddress, DataAddress, MapAddress, Scope, SequenceAddress, XorName};
+
+    #[test]
+    fn data_address_scope() {
+        let name = XorName::random();
+        let scope = |address: DataAddress| address.scope();
+        assert_eq!(scope(BlobAddress::Public(name).into()), Scope::Public);
+        assert_eq!(scope(BlobAddress::Private(name).into()), Scope::Private);
+        assert_eq!(
+            scope(MapAddress::Seq { name, tag: 0 }.into()),
+            Scope::Private
+        );
+        assert_eq!(
+            scope(SequenceAddress::Public { name, tag: 0 }.into()),
+            Scope::Public
+        );
+        assert_eq!(
+            scope(SequenceAddress::Private { name, tag: 0 }.into()),
+            Scope::Private
+        );
+    }
+
+    #[cfg(all(feature = "blob", feature = "map", feature = "sequence"))]
+    #[test]
+    fn data_metadata() -> crate::Result<()> {
+        use super::{
+            Blob, BlobStorageClass, Data, Error, Keypair, Map, PrivateBlob, PublicBlob, PublicKey,
+            SeqMap, Sequence, SignedWrite,
+        };
+        use crate::errors::convert_bincode_error;
+        use rand::rngs::OsRng;
+        use threshold_crypto::SecretKey;
+
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let name = XorName::random();
+
+        let storage_class = BlobStorageClass::new(5)?;
+        let blob = Blob::from(PublicBlob::new(b"hello".to_vec()).with_storage_class(storage_class));
+        let metadata = Data::from(blob.clone()).metadata()?;
+        assert_eq!(metadata.address, DataAddress::Blob(*blob.address()));
+        assert_eq!(metadata.owner, None);
+        assert_eq!(metadata.version, None);
+        assert_eq!(metadata.size, blob.serialised_size());
+        assert_eq!(metadata.created, None);
+        assert_eq!(metadata.storage_class, Some(storage_class));
+
+        let blob = Blob::from(PrivateBlob::new(b"hello".to_vec(), owner));
+        let metadata = Data::from(blob).metadata()?;
+        assert_eq!(metadata.owner, Some(owner));
+        assert_eq!(metadata.storage_class, Some(BlobStorageClass::default()));
+
+        let map = Map::from(SeqMap::new(name, 15_000, owner));
+        let metadata = Data::from(map.clone()).metadata()?;
+        assert_eq!(metadata.address, DataAddress::Map(*map.address()));
+        assert_eq!(metadata.owner, Some(owner));
+        assert_eq!(metadata.version, Some(0));
+        assert_eq!(metadata.size, map.serialised_size());
+        assert_eq!(metadata.storage_class, None);
+
+        let entries = vec![b"a".to_vec(), b"b".to_vec()];
+        let sequence = Sequence::new_public_with_data(
+            owner,
+            "actor".to_string(),
+            name,
+            15_000,
+            None,
+            entries,
+        )?;
+        let metadata = Data::from(sequence.clone()).metadata()?;
+        assert_eq!(metadata.address, DataAddress::Sequence(*sequence.address()));
+        assert_eq!(metadata.owner, Some(owner));
+        assert_eq!(metadata.version, Some(2));
+        assert_eq!(
+            metadata.size,
+            bincode::serialized_size(&sequence).map_err(convert_bincode_error)?
+        );
+        assert_eq!(metadata.storage_class, None);
+
+        // The entries of a deleted Sequence can no longer be counted.
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+        let mut sequence = Sequence::new_private(
+            keypair.public_key(),
+            "actor".to_string(),
+            name,
+            15_000,
+            None,
+        );
+        sequence.delete(&SignedWrite::new(*sequence.address(), &keypair)?)?;
+        assert_eq!(Data::from(sequence).metadata(), Err(Error::DataDeleted));
+        Ok(())
+    }
+}
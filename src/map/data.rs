@@ -0,0 +1,1882 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{Action, Address, Kind};
+use crate::{DeniedAction, Error, PublicKey, Result, Scope, SignedWrite};
+use bincode::serialized_size;
+use hex_fmt::HexFmt;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{btree_map::Entry, BTreeMap, BTreeSet},
+    fmt::{self, Debug, Formatter},
+    mem,
+};
+use xor_name::XorName;
+
+/// Map that is unpublished on the network. This data can only be fetched by the owner or
+/// those in the permissions fields with `Permission::Read` access.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+pub struct SeqData {
+    /// Network address.
+    address: Address,
+    /// Key-Value semantics.
+    data: SeqEntries,
+    /// Maps an application key to a list of allowed or forbidden actions.
+    permissions: BTreeMap<PublicKey, PermissionSet>,
+    /// Version should be increased for any changes to Map fields except for data.
+    version: u64,
+    /// Contains the public key of an owner or owners of this data.
+    ///
+    /// Data Handlers in nodes enforce that a mutation request has a valid signature of the owner.
+    owner: PublicKey,
+    /// The signed permission and ownership changes, oldest first.
+    #[serde(default)]
+    permission_history: Vec<SignedWrite<PermissionOp>>,
+}
+
+impl Debug for SeqData {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "SeqMap {:?}", self.name())
+    }
+}
+
+/// Map that is unpublished on the network. This data can only be fetched by the owner or
+/// those in the permissions fields with `Permission::Read` access.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+pub struct UnseqData {
+    /// Network address.
+    address: Address,
+    /// Key-Value semantics.
+    data: UnseqEntries,
+    /// Maps an application key to a list of allowed or forbidden actions.
+    permissions: BTreeMap<PublicKey, PermissionSet>,
+    /// Version should be increased for any changes to Map fields except for data.
+    version: u64,
+    /// Contains the public key of an owner or owners of this data.
+    ///
+    /// Data Handlers in nodes enforce that a mutation request has a valid signature of the owner.
+    owner: PublicKey,
+    /// The signed permission and ownership changes, oldest first.
+    #[serde(default)]
+    permission_history: Vec<SignedWrite<PermissionOp>>,
+}
+
+impl Debug for UnseqData {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "UnseqMap {:?}", self.name())
+    }
+}
+
+/// A value in sequenced Map.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+pub struct SeqValue {
+    /// Actual data.
+    pub data: Vec<u8>,
+    /// Version, incremented sequentially for any change to `data`.
+    pub version: u64,
+}
+
+impl Debug for SeqValue {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:<8} :: {}", HexFmt(&self.data), self.version)
+    }
+}
+
+/// Wrapper type for values, which can be sequenced or unsequenced.
+#[derive(Debug, Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+pub enum Value {
+    /// Sequenced value.
+    Seq(SeqValue),
+    /// Unsequenced value.
+    Unseq(Vec<u8>),
+}
+
+impl From<SeqValue> for Value {
+    fn from(value: SeqValue) -> Self {
+        Value::Seq(value)
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(value: Vec<u8>) -> Self {
+        Value::Unseq(value)
+    }
+}
+
+/// Wrapper type for lists of sequenced or unsequenced values.
+#[derive(Debug, Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+pub enum Values {
+    /// List of sequenced values.
+    Seq(Vec<SeqValue>),
+    /// List of unsequenced values.
+    Unseq(Vec<Vec<u8>>),
+}
+
+impl From<Vec<SeqValue>> for Values {
+    fn from(values: Vec<SeqValue>) -> Self {
+        Values::Seq(values)
+    }
+}
+
+impl From<Vec<Vec<u8>>> for Values {
+    fn from(values: Vec<Vec<u8>>) -> Self {
+        Values::Unseq(values)
+    }
+}
+
+/// Set of user permissions.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize, Default)]
+pub struct PermissionSet {
+    permissions: BTreeSet<Action>,
+}
+
+impl PermissionSet {
+    /// Constructs new permission set.
+    pub fn new() -> PermissionSet {
+        PermissionSet {
+            permissions: Default::default(),
+        }
+    }
+
+    /// Allows the given action.
+    pub fn allow(mut self, action: Action) -> Self {
+        let _ = self.permissions.insert(action);
+        self
+    }
+
+    /// Denies the given action.
+    pub fn deny(mut self, action: Action) -> Self {
+        let _ = self.permissions.remove(&action);
+        self
+    }
+
+    /// Is the given action allowed according to this permission set?
+    pub fn is_allowed(&self, action: Action) -> bool {
+        self.permissions.contains(&action)
+    }
+
+    /// Merges with another permission set, allowing the actions allowed by either.
+    /// The merge is commutative, so concurrent grants reconcile to the same set.
+    pub fn merge(&self, other: &PermissionSet) -> Self {
+        PermissionSet {
+            permissions: self
+                .permissions
+                .union(&other.permissions)
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// Returns the changes which turn this permission set into `other`.
+    pub fn diff(&self, other: &PermissionSet) -> PermissionDelta {
+        PermissionDelta {
+            allow: other
+                .permissions
+                .difference(&self.permissions)
+                .copied()
+                .collect(),
+            deny: self
+                .permissions
+                .difference(&other.permissions)
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// Applies the changes of a delta. An action both allowed and denied by the
+    /// delta is denied.
+    pub fn apply(mut self, delta: &PermissionDelta) -> Self {
+        self.permissions.extend(delta.allow.iter().copied());
+        for action in &delta.deny {
+            let _ = self.permissions.remove(action);
+        }
+        self
+    }
+}
+
+/// Changes to a `PermissionSet`, as returned by `PermissionSet::diff`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize, Default)]
+pub struct PermissionDelta {
+    /// Actions to allow.
+    pub allow: BTreeSet<Action>,
+    /// Actions to deny.
+    pub deny: BTreeSet<Action>,
+}
+
+impl PermissionDelta {
+    /// Returns true if the delta makes no changes.
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+}
+
+/// A change to the permissions or the owner of a Map.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum PermissionChange {
+    /// Sets the permissions of a user.
+    SetUserPermissions {
+        /// The user.
+        user: PublicKey,
+        /// The new permissions.
+        permissions: PermissionSet,
+    },
+    /// Deletes the permissions of a user.
+    DelUserPermissions(PublicKey),
+    /// Changes the owner.
+    ChangeOwner(PublicKey),
+}
+
+/// A change to the permissions or the owner of the Map at `address`, taking the
+/// Map fields to `version`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PermissionOp {
+    /// Address of the Map.
+    pub address: Address,
+    /// The change.
+    pub change: PermissionChange,
+    /// The new version of the Map fields.
+    pub version: u64,
+}
+
+/// Maximum allowed size for a serialised Map to grow to.
+pub const MAX_MAP_SIZE_IN_BYTES: u64 = 1024 * 1024;
+
+macro_rules! impl_map {
+    ($flavour:ident) => {
+        impl $flavour {
+            /// Returns the address.
+            pub fn address(&self) -> &Address {
+                &self.address
+            }
+
+            /// Returns size of this data after serialisation.
+            pub fn serialised_size(&self) -> u64 {
+                serialized_size(self).unwrap_or(u64::MAX)
+            }
+
+            /// Returns true if the size is valid.
+            pub fn validate_size(&self) -> bool {
+                self.serialised_size() <= MAX_MAP_SIZE_IN_BYTES
+            }
+
+            /// Returns the serialised size of the entry with the given key, if present.
+            pub fn entry_size(&self, key: &[u8]) -> Option<u64> {
+                self.data
+                    .get_key_value(key)
+                    .map(|entry| serialized_size(&entry).unwrap_or(u64::MAX))
+            }
+
+            /// Returns `Err(ExceededSize)` if holding `entries` in place of the current ones
+            /// would take the data over `MAX_MAP_SIZE_IN_BYTES`.
+            fn check_size_with<T: Serialize>(&self, entries: &T) -> Result<()> {
+                let current = serialized_size(&self.data).unwrap_or(u64::MAX);
+                let new = serialized_size(entries).unwrap_or(u64::MAX);
+                let size = self
+                    .serialised_size()
+                    .saturating_sub(current)
+                    .saturating_add(new);
+                if size > MAX_MAP_SIZE_IN_BYTES {
+                    Err(Error::ExceededSize)
+                } else {
+                    Ok(())
+                }
+            }
+
+            /// Returns the name.
+            pub fn name(&self) -> &XorName {
+                self.address.name()
+            }
+
+            /// Returns the tag type.
+            pub fn tag(&self) -> u64 {
+                self.address.tag()
+            }
+
+            /// Returns the kind.
+            pub fn kind(&self) -> Kind {
+                self.address.kind()
+            }
+
+            /// Returns the scope, which is always private for Map.
+            pub fn scope(&self) -> Scope {
+                self.address.scope()
+            }
+
+            /// Returns the version of the Map fields (not the data version).
+            pub fn version(&self) -> u64 {
+                self.version
+            }
+
+            /// Returns the owner key.
+            pub fn owner(&self) -> &PublicKey {
+                &self.owner
+            }
+
+            /// Returns all the keys in the data.
+            pub fn keys(&self) -> BTreeSet<Vec<u8>> {
+                self.data.keys().cloned().collect()
+            }
+
+            /// Returns the shell of this Map (the fields without the data).
+            pub fn shell(&self) -> Self {
+                Self {
+                    address: self.address.clone(),
+                    data: BTreeMap::new(),
+                    permissions: self.permissions.clone(),
+                    version: self.version,
+                    owner: self.owner,
+                    permission_history: self.permission_history.clone(),
+                }
+            }
+
+            /// Gets a complete list of permissions.
+            pub fn permissions(&self) -> BTreeMap<PublicKey, PermissionSet> {
+                self.permissions.clone()
+            }
+
+            /// Gets the permissions for the provided user.
+            pub fn user_permissions(&self, user: &PublicKey) -> Result<&PermissionSet> {
+                self.permissions.get(user).ok_or(Error::NoSuchKey)
+            }
+
+            /// Checks if the provided user is an owner.
+            ///
+            /// Returns `Ok(())` on success and `Err(Error::AccessDenied)` if the user is not an
+            /// owner.
+            pub fn check_is_owner(&self, requester: &PublicKey) -> Result<()> {
+                if &self.owner == requester {
+                    Ok(())
+                } else {
+                    Err(Error::access_denied(
+                        *requester,
+                        DeniedAction::Owner,
+                        Some(self.address.into()),
+                    ))
+                }
+            }
+
+            /// Checks permissions for given `action` for the provided user.
+            ///
+            /// Returns `Err(Error::AccessDenied)` if the permission check has failed.
+            pub fn check_permissions(&self, action: Action, requester: &PublicKey) -> Result<()> {
+                if &self.owner == requester {
+                    Ok(())
+                } else {
+                    let denied = || {
+                        Error::access_denied(
+                            *requester,
+                            DeniedAction::Map(action),
+                            Some(self.address.into()),
+                        )
+                    };
+                    let permissions = self.user_permissions(requester).map_err(|_| denied())?;
+                    if permissions.is_allowed(action) {
+                        Ok(())
+                    } else {
+                        Err(denied())
+                    }
+                }
+            }
+
+            // Inserts or updates permissions for the provided user.
+            //
+            // Requires the new `version` of the Map fields. If it does not match the
+            // current version + 1, an error will be returned.
+            fn set_user_permissions(
+                &mut self,
+                user: PublicKey,
+                permissions: PermissionSet,
+                version: u64,
+            ) -> Result<()> {
+                if version != self.version + 1 {
+                    return Err(Error::InvalidSuccessor(self.version));
+                }
+
+                let _prev = self.permissions.insert(user, permissions);
+                self.version = version;
+
+                Ok(())
+            }
+
+            // Deletes permissions for the provided user.
+            //
+            // Requires the new `version` of the Map fields. If it does not match the
+            // current version + 1, an error will be returned.
+            fn del_user_permissions(&mut self, user: PublicKey, version: u64) -> Result<()> {
+                if version != self.version + 1 {
+                    return Err(Error::InvalidSuccessor(self.version));
+                }
+                if !self.permissions.contains_key(&user) {
+                    return Err(Error::NoSuchKey);
+                }
+
+                let _ = self.permissions.remove(&user);
+                self.version = version;
+
+                Ok(())
+            }
+
+            // Changes the owner.
+            //
+            // Requires the new `version` of the Map fields. If it does not match the
+            // current version + 1, an error will be returned.
+            fn change_owner(&mut self, new_owner: PublicKey, version: u64) -> Result<()> {
+                if version != self.version + 1 {
+                    return Err(Error::InvalidSuccessor(self.version));
+                }
+
+                self.owner = new_owner;
+                self.version = version;
+
+                Ok(())
+            }
+
+            /// Returns true if `action` is allowed for the provided user.
+            pub fn is_action_allowed(&self, requester: &PublicKey, action: Action) -> bool {
+                match self.permissions.get(requester) {
+                    Some(perms) => perms.is_allowed(action),
+                    None => false,
+                }
+            }
+
+            /// Applies a signed change to the permissions or the owner, recording it in
+            /// the permission history. Changing the owner is reserved to the owner, while
+            /// changing permissions requires `Action::ManagePermissions`. This is the only
+            /// way to change the permissions or the owner once the Map is created.
+            ///
+            /// Returns `Err(ExceededSize)` if the Map, with the change recorded, would
+            /// exceed `MAX_MAP_SIZE_IN_BYTES`.
+            pub fn apply_permission_op(
+                &mut self,
+                request: SignedWrite<PermissionOp>,
+            ) -> Result<()> {
+                if request.op.address != self.address {
+                    return Err(Error::InvalidOperation);
+                }
+                request.verify()?;
+                match &request.op.change {
+                    PermissionChange::SetUserPermissions { .. }
+                    | PermissionChange::DelUserPermissions(_) => {
+                        self.check_permissions(Action::ManagePermissions, &request.requester)?
+                    }
+                    PermissionChange::ChangeOwner(_) => self.check_is_owner(&request.requester)?,
+                }
+
+                let mut updated = self.shell();
+                let version = request.op.version;
+                match &request.op.change {
+                    PermissionChange::SetUserPermissions { user, permissions } => {
+                        updated.set_user_permissions(*user, permissions.clone(), version)?
+                    }
+                    PermissionChange::DelUserPermissions(user) => {
+                        updated.del_user_permissions(*user, version)?
+                    }
+                    PermissionChange::ChangeOwner(new_owner) => {
+                        updated.change_owner(*new_owner, version)?
+                    }
+                }
+                updated.permission_history.push(request);
+                updated.check_size_with(&self.data)?;
+                updated.data = std::mem::take(&mut self.data);
+                *self = updated;
+                Ok(())
+            }
+
+            /// Applies a change already validated elsewhere, e.g. by the other replicas,
+            /// recording it in the permission history without checking its signature or
+            /// permissions. Returns false, leaving the Map unchanged, unless the change
+            /// is to this Map and to the version following that of its fields.
+            pub fn apply_permission_op_without_validation(
+                &mut self,
+                request: SignedWrite<PermissionOp>,
+            ) -> bool {
+                let version = request.op.version;
+                if request.op.address != self.address
+                    || Some(version) != self.version.checked_add(1)
+                {
+                    return false;
+                }
+
+                match &request.op.change {
+                    PermissionChange::SetUserPermissions { user, permissions } => {
+                        let _ = self.permissions.insert(*user, permissions.clone());
+                    }
+                    PermissionChange::DelUserPermissions(user) => {
+                        let _ = self.permissions.remove(user);
+                    }
+                    PermissionChange::ChangeOwner(new_owner) => self.owner = *new_owner,
+                }
+                self.version = version;
+                self.permission_history.push(request);
+
+                true
+            }
+
+            /// Returns the changes applied with `apply_permission_op`, oldest first.
+            pub fn permission_history(&self) -> &[SignedWrite<PermissionOp>] {
+                &self.permission_history
+            }
+        }
+    };
+}
+
+impl_map!(SeqData);
+impl_map!(UnseqData);
+
+impl UnseqData {
+    /// Creates a new unsequenced Map.
+    pub fn new(name: XorName, tag: u64, owner: PublicKey) -> Self {
+        Self {
+            address: Address::Unseq { name, tag },
+            data: Default::default(),
+            permissions: Default::default(),
+            version: 0,
+            owner,
+            permission_history: Vec::new(),
+        }
+    }
+
+    /// Creates a new unsequenced Map with entries and permissions.
+    pub fn new_with_data(
+        name: XorName,
+        tag: u64,
+        data: UnseqEntries,
+        permissions: BTreeMap<PublicKey, PermissionSet>,
+        owner: PublicKey,
+    ) -> Self {
+        Self {
+            address: Address::Unseq { name, tag },
+            data,
+            permissions,
+            version: 0,
+            owner,
+            permission_history: Vec::new(),
+        }
+    }
+
+    /// Returns a value for the given key.
+    pub fn get(&self, key: &[u8]) -> Option<&Vec<u8>> {
+        self.data.get(key)
+    }
+
+    /// Returns values of all entries.
+    pub fn values(&self) -> Vec<Vec<u8>> {
+        self.data.values().cloned().collect()
+    }
+
+    /// Returns all entries.
+    pub fn entries(&self) -> &UnseqEntries {
+        &self.data
+    }
+
+    /// Removes and returns all entries.
+    pub fn take_entries(&mut self) -> UnseqEntries {
+        mem::replace(&mut self.data, BTreeMap::new())
+    }
+
+    /// Mutates entries based on `actions` for the provided user.
+    ///
+    /// Returns `Err(InvalidEntryActions)` if the mutation parameters are invalid.
+    pub fn mutate_entries(
+        &mut self,
+        actions: UnseqEntryActions,
+        requester: &PublicKey,
+    ) -> Result<()> {
+        let (insert, update, delete) = actions.actions.into_iter().fold(
+            (
+                BTreeMap::<Vec<u8>, Vec<u8>>::new(),
+                BTreeMap::<Vec<u8>, Vec<u8>>::new(),
+                BTreeSet::<Vec<u8>>::new(),
+            ),
+            |(mut insert, mut update, mut delete), (key, item)| {
+                match item {
+                    UnseqEntryAction::Ins(value) => {
+                        let _ = insert.insert(key, value);
+                    }
+                    UnseqEntryAction::Update(value) => {
+                        let _ = update.insert(key, value);
+                    }
+                    UnseqEntryAction::Del => {
+                        let _ = delete.insert(key);
+                    }
+                };
+                (insert, update, delete)
+            },
+        );
+
+        if self.owner() != requester {
+            let denied = [
+                (!insert.is_empty(), Action::Insert),
+                (!update.is_empty(), Action::Update),
+                (!delete.is_empty(), Action::Delete),
+            ]
+            .iter()
+            .find(|(requested, action)| *requested && !self.is_action_allowed(requester, *action))
+            .map(|(_, action)| *action);
+            if let Some(action) = denied {
+                return Err(Error::access_denied(
+                    *requester,
+                    DeniedAction::Map(action),
+                    Some(self.address.into()),
+                ));
+            }
+        }
+
+        let mut new_data = self.data.clone();
+        let mut errors = BTreeMap::new();
+
+        for (key, val) in insert {
+            match new_data.entry(key) {
+                Entry::Occupied(entry) => {
+                    let _ = errors.insert(entry.key().clone(), Error::EntryExists(0));
+                }
+                Entry::Vacant(entry) => {
+                    let _ = entry.insert(val);
+                }
+            }
+        }
+
+        for (key, val) in update {
+            match new_data.entry(key) {
+                Entry::Occupied(mut entry) => {
+                    let _ = entry.insert(val);
+                }
+                Entry::Vacant(entry) => {
+                    let _ = errors.insert(entry.key().clone(), Error::NoSuchEntry);
+                }
+            }
+        }
+
+        for key in delete {
+            match new_data.entry(key.clone()) {
+                Entry::Occupied(_) => {
+                    let _ = new_data.remove(&key);
+                }
+                Entry::Vacant(entry) => {
+                    let _ = errors.insert(entry.key().clone(), Error::NoSuchEntry);
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(Error::InvalidEntryActions(errors));
+        }
+        self.check_size_with(&new_data)?;
+
+        let _old_data = mem::replace(&mut self.data, new_data);
+
+        Ok(())
+    }
+}
+
+/// Implements functions for sequenced Map.
+impl SeqData {
+    /// Creates a new sequenced Map.
+    pub fn new(name: XorName, tag: u64, owner: PublicKey) -> Self {
+        Self {
+            address: Address::Seq { name, tag },
+            data: Default::default(),
+            permissions: Default::default(),
+            version: 0,
+            owner,
+            permission_history: Vec::new(),
+        }
+    }
+
+    /// Creates a new sequenced Map with entries and permissions.
+    pub fn new_with_data(
+        name: XorName,
+        tag: u64,
+        data: SeqEntries,
+        permissions: BTreeMap<PublicKey, PermissionSet>,
+        owner: PublicKey,
+    ) -> Self {
+        Self {
+            address: Address::Seq { name, tag },
+            data,
+            permissions,
+            version: 0,
+            owner,
+            permission_history: Vec::new(),
+        }
+    }
+
+    /// Returns a value by the given key
+    pub fn get(&self, key: &[u8]) -> Option<&SeqValue> {
+        self.data.get(key)
+    }
+
+    /// Returns values of all entries
+    pub fn values(&self) -> Vec<SeqValue> {
+        self.data.values().cloned().collect()
+    }
+
+    /// Returns all entries
+    pub fn entries(&self) -> &SeqEntries {
+        &self.data
+    }
+
+    /// Removes and returns all entries
+    pub fn take_entries(&mut self) -> SeqEntries {
+        mem::replace(&mut self.data, BTreeMap::new())
+    }
+
+    /// Mutates entries (key + value pairs) in bulk.
+    ///
+    /// Returns `Err(InvalidEntryActions)` if the mutation parameters are invalid.
+    pub fn mutate_entries(
+        &mut self,
+        actions: SeqEntryActions,
+        requester: &PublicKey,
+    ) -> Result<()> {
+        // Deconstruct actions into inserts, updates, and deletes
+        let (insert, update, delete) = actions.actions.into_iter().fold(
+            (BTreeMap::new(), BTreeMap::new(), BTreeMap::new()),
+            |(mut insert, mut update, mut delete), (key, item)| {
+                match item {
+                    SeqEntryAction::Ins(value) => {
+                        let _ = insert.insert(key, value);
+                    }
+                    SeqEntryAction::Update(value) => {
+                        let _ = update.insert(key, value);
+                    }
+                    SeqEntryAction::Del(version) => {
+                        let _ = delete.insert(key, version);
+                    }
+                };
+                (insert, update, delete)
+            },
+        );
+
+        if self.owner() != requester {
+            let denied = [
+                (!insert.is_empty(), Action::Insert),
+                (!update.is_empty(), Action::Update),
+                (!delete.is_empty(), Action::Delete),
+            ]
+            .iter()
+            .find(|(requested, action)| *requested && !self.is_action_allowed(requester, *action))
+            .map(|(_, action)| *action);
+            if let Some(action) = denied {
+                return Err(Error::access_denied(
+                    *requester,
+                    DeniedAction::Map(action),
+                    Some(self.address.into()),
+                ));
+            }
+        }
+
+        let mut new_data = self.data.clone();
+        let mut errors = BTreeMap::new();
+
+        for (key, val) in insert {
+            match new_data.entry(key) {
+                Entry::Occupied(entry) => {
+                    let _ = errors.insert(
+                        entry.key().clone(),
+                        Error::EntryExists(entry.get().version as u8),
+                    );
+                }
+                Entry::Vacant(entry) => {
+                    let _ = entry.insert(val);
+                }
+            }
+        }
+
+        for (key, val) in update {
+            match new_data.entry(key) {
+                Entry::Occupied(mut entry) => {
+                    let current_version = entry.get().version;
+                    if val.version == current_version + 1 {
+                        let _ = entry.insert(val);
+                    } else {
+                        let _ = errors.insert(
+                            entry.key().clone(),
+                            Error::InvalidSuccessor(current_version),
+                        );
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    let _ = errors.insert(entry.key().clone(), Error::NoSuchEntry);
+                }
+            }
+        }
+
+        for (key, version) in delete {
+            match new_data.entry(key.clone()) {
+                Entry::Occupied(entry) => {
+                    let current_version = entry.get().version;
+                    if version == current_version + 1 {
+                        let _ = new_data.remove(&key);
+                    } else {
+                        let _ = errors.insert(
+                            entry.key().clone(),
+                            Error::InvalidSuccessor(current_version),
+                        );
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    let _ = errors.insert(entry.key().clone(), Error::NoSuchEntry);
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(Error::InvalidEntryActions(errors));
+        }
+        self.check_size_with(&new_data)?;
+
+        let _old_data = mem::replace(&mut self.data, new_data);
+
+        Ok(())
+    }
+
+    /// Mutates entries like `mutate_entries`, and records the applied actions in the
+    /// per-key `histories`, from which past values can be served.
+    ///
+    /// Keys without a history yet start it from their current value. Neither the data
+    /// nor the histories are changed if an action fails.
+    pub fn mutate_entries_with_history(
+        &mut self,
+        actions: SeqEntryActions,
+        requester: &PublicKey,
+        histories: &mut BTreeMap<Vec<u8>, ValueHistory>,
+    ) -> Result<()> {
+        let mut recorded = BTreeMap::new();
+        for (key, action) in actions.actions() {
+            let mut history = histories
+                .get(key)
+                .cloned()
+                .unwrap_or_else(|| ValueHistory::starting_at(self.data.get(key)));
+            if history.record(action.clone()).is_ok() {
+                let _ = recorded.insert(key.clone(), history);
+            }
+        }
+        let recorded_all = recorded.len() == actions.actions().len();
+        let mut mutated = self.clone();
+        mutated.mutate_entries(actions, requester)?;
+        // The actions apply to the data, so a history refusing one is out of step with it:
+        // the caller must change the entries with a history only through this method.
+        if !recorded_all {
+            return Err(Error::InvalidOperation);
+        }
+        *self = mutated;
+        histories.extend(recorded);
+        Ok(())
+    }
+}
+
+/// Object storing a Map variant.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum Data {
+    /// Sequenced Map.
+    Seq(SeqData),
+    /// Unsequenced Map.
+    Unseq(UnseqData),
+}
+
+impl Data {
+    /// Returns the address of the data.
+    pub fn address(&self) -> &Address {
+        match self {
+            Data::Seq(data) => data.address(),
+            Data::Unseq(data) => data.address(),
+        }
+    }
+
+    /// Returns the kind of the data.
+    pub fn kind(&self) -> Kind {
+        self.address().kind()
+    }
+
+    /// Returns the scope of the data, which is always private for Map.
+    pub fn scope(&self) -> Scope {
+        self.address().scope()
+    }
+
+    /// Returns the name of the data.
+    pub fn name(&self) -> &XorName {
+        self.address().name()
+    }
+
+    /// Returns the tag of the data.
+    pub fn tag(&self) -> u64 {
+        self.address().tag()
+    }
+
+    /// Returns true if the data is sequenced.
+    pub fn is_seq(&self) -> bool {
+        self.kind().is_seq()
+    }
+
+    /// Returns true if the data is unsequenced.
+    pub fn is_unseq(&self) -> bool {
+        self.kind().is_unseq()
+    }
+
+    /// Returns size of this data after serialisation.
+    pub fn serialised_size(&self) -> u64 {
+        match self {
+            Data::Seq(data) => data.serialised_size(),
+            Data::Unseq(data) => data.serialised_size(),
+        }
+    }
+
+    /// Returns true if the size is valid.
+    pub fn validate_size(&self) -> bool {
+        self.serialised_size() <= MAX_MAP_SIZE_IN_BYTES
+    }
+
+    /// Returns the version of this data.
+    pub fn version(&self) -> u64 {
+        match self {
+            Data::Seq(data) => data.version(),
+            Data::Unseq(data) => data.version(),
+        }
+    }
+
+    /// Returns all the keys in the data.
+    pub fn keys(&self) -> BTreeSet<Vec<u8>> {
+        match self {
+            Data::Seq(data) => data.keys(),
+            Data::Unseq(data) => data.keys(),
+        }
+    }
+
+    /// Returns the value of each of the given keys, or `Error::NoSuchEntry` for keys
+    /// which are not present, so that several keys can be fetched at once.
+    pub fn get_values(&self, keys: &[Vec<u8>]) -> BTreeMap<Vec<u8>, Result<Value>> {
+        keys.iter()
+            .map(|key| {
+                let value = match self {
+                    Data::Seq(data) => data.get(key).cloned().map(Value::from),
+                    Data::Unseq(data) => data.get(key).cloned().map(Value::from),
+                };
+                (key.clone(), value.ok_or(Error::NoSuchEntry))
+            })
+            .collect()
+    }
+
+    /// Returns the shell of the data.
+    pub fn shell(&self) -> Self {
+        match self {
+            Data::Seq(data) => Data::Seq(data.shell()),
+            Data::Unseq(data) => Data::Unseq(data.shell()),
+        }
+    }
+
+    /// Gets a complete list of permissions.
+    pub fn permissions(&self) -> BTreeMap<PublicKey, PermissionSet> {
+        match self {
+            Data::Seq(data) => data.permissions(),
+            Data::Unseq(data) => data.permissions(),
+        }
+    }
+
+    /// Gets the permissions for the provided user.
+    pub fn user_permissions(&self, user: &PublicKey) -> Result<&PermissionSet> {
+        match self {
+            Data::Seq(data) => data.user_permissions(user),
+            Data::Unseq(data) => data.user_permissions(user),
+        }
+    }
+
+    /// Applies a signed change to the permissions or the owner, recording it in
+    /// the permission history.
+    pub fn apply_permission_op(&mut self, request: SignedWrite<PermissionOp>) -> Result<()> {
+        match self {
+            Data::Seq(data) => data.apply_permission_op(request),
+            Data::Unseq(data) => data.apply_permission_op(request),
+        }
+    }
+
+    /// Returns the signed permission and ownership changes, oldest first.
+    pub fn permission_history(&self) -> &[SignedWrite<PermissionOp>] {
+        match self {
+            Data::Seq(data) => data.permission_history(),
+            Data::Unseq(data) => data.permission_history(),
+        }
+    }
+
+    /// Checks permissions for given `action` for the provided user.
+    pub fn check_permissions(&self, action: Action, requester: &PublicKey) -> Result<()> {
+        match self {
+            Data::Seq(data) => data.check_permissions(action, requester),
+            Data::Unseq(data) => data.check_permissions(action, requester),
+        }
+    }
+
+    /// Checks if the provided user is an owner.
+    pub fn check_is_owner(&self, requester: &PublicKey) -> Result<()> {
+        match self {
+            Data::Seq(data) => data.check_is_owner(requester),
+            Data::Unseq(data) => data.check_is_owner(requester),
+        }
+    }
+
+    /// Returns the owner key.
+    pub fn owner(&self) -> PublicKey {
+        match self {
+            Data::Seq(data) => data.owner,
+            Data::Unseq(data) => data.owner,
+        }
+    }
+
+    /// Mutates entries (key + value pairs) in bulk.
+    pub fn mutate_entries(&mut self, actions: EntryActions, requester: &PublicKey) -> Result<()> {
+        match self {
+            Data::Seq(data) => {
+                if let EntryActions::Seq(actions) = actions {
+                    return data.mutate_entries(actions, requester);
+                }
+            }
+            Data::Unseq(data) => {
+                if let EntryActions::Unseq(actions) = actions {
+                    return data.mutate_entries(actions, requester);
+                }
+            }
+        }
+
+        Err(Error::InvalidOperation)
+    }
+}
+
+impl From<SeqData> for Data {
+    fn from(data: SeqData) -> Self {
+        Data::Seq(data)
+    }
+}
+
+impl From<UnseqData> for Data {
+    fn from(data: UnseqData) -> Self {
+        Data::Unseq(data)
+    }
+}
+
+/// Action for a sequenced Entry.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
+pub enum SeqEntryAction {
+    /// Inserts a new sequenced entry.
+    Ins(SeqValue),
+    /// Updates an entry with a new value and version.
+    Update(SeqValue),
+    /// Deletes an entry.
+    Del(u64),
+}
+
+impl SeqEntryAction {
+    /// Returns the version for this action.
+    pub fn version(&self) -> u64 {
+        match *self {
+            Self::Ins(ref value) => value.version,
+            Self::Update(ref value) => value.version,
+            Self::Del(v) => v,
+        }
+    }
+
+    /// Sets the version for this action.
+    pub fn set_version(&mut self, version: u64) {
+        match *self {
+            Self::Ins(ref mut value) => value.version = version,
+            Self::Update(ref mut value) => value.version = version,
+            Self::Del(ref mut v) => *v = version,
+        }
+    }
+}
+
+/// A past state of a sequenced Map entry.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
+pub struct ValueHistoryEntry {
+    /// Version of the entry at this point.
+    pub version: u64,
+    /// Value of the entry at this point, empty if deleted.
+    pub data: Vec<u8>,
+    /// `true` if the entry was deleted at this version.
+    pub deleted: bool,
+}
+
+/// History of the values of a key in sequenced Map, oldest first.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug, Default)]
+pub struct ValueHistory {
+    entries: Vec<ValueHistoryEntry>,
+}
+
+impl ValueHistory {
+    /// Constructs an empty history.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    // Starts the history of a key from its current value, if any.
+    fn starting_at(value: Option<&SeqValue>) -> Self {
+        Self {
+            entries: value
+                .map(|value| ValueHistoryEntry {
+                    version: value.version,
+                    data: value.data.clone(),
+                    deleted: false,
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Records an action applied to the key, following the same rules as
+    /// `SeqData::mutate_entries`.
+    pub fn record(&mut self, action: SeqEntryAction) -> Result<()> {
+        let current = self.entries.last().filter(|entry| !entry.deleted);
+        let entry = match (action, current) {
+            (SeqEntryAction::Ins(value), None) => ValueHistoryEntry {
+                version: value.version,
+                data: value.data,
+                deleted: false,
+            },
+            (SeqEntryAction::Ins(_), Some(current)) => {
+                return Err(Error::EntryExists(current.version as u8))
+            }
+            (SeqEntryAction::Update(value), Some(current)) => {
+                if value.version != current.version + 1 {
+                    return Err(Error::InvalidSuccessor(current.version));
+                }
+                ValueHistoryEntry {
+                    version: value.version,
+                    data: value.data,
+                    deleted: false,
+                }
+            }
+            (SeqEntryAction::Del(version), Some(current)) => {
+                if version != current.version + 1 {
+                    return Err(Error::InvalidSuccessor(current.version));
+                }
+                ValueHistoryEntry {
+                    version,
+                    data: Vec::new(),
+                    deleted: true,
+                }
+            }
+            (_, None) => return Err(Error::NoSuchEntry),
+        };
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Returns all the recorded states, oldest first.
+    pub fn entries(&self) -> &[ValueHistoryEntry] {
+        &self.entries
+    }
+
+    /// Returns the most recent state of the entry.
+    pub fn latest(&self) -> Option<&ValueHistoryEntry> {
+        self.entries.last()
+    }
+
+    /// Returns the most recent state of the entry with the given version.
+    pub fn at_version(&self, version: u64) -> Option<&ValueHistoryEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| entry.version == version)
+    }
+}
+
+/// Action for an unsequenced Entry.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
+pub enum UnseqEntryAction {
+    /// Inserts a new unsequenced entry.
+    Ins(Vec<u8>),
+    /// Updates an entry with a new value.
+    Update(Vec<u8>),
+    /// Deletes an entry.
+    Del,
+}
+
+/// Sequenced Entry Actions for given entry keys.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug, Default)]
+pub struct SeqEntryActions {
+    // A map containing keys and corresponding sequenced entry actions to perform.
+    actions: BTreeMap<Vec<u8>, SeqEntryAction>,
+}
+
+impl SeqEntryActions {
+    /// Creates a new sequenced Entry Actions list.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns a builder validating the actions as they are added.
+    pub fn builder() -> EntryActionsBuilder<SeqEntryAction> {
+        Default::default()
+    }
+
+    /// Gets the actions.
+    pub fn actions(&self) -> &BTreeMap<Vec<u8>, SeqEntryAction> {
+        &self.actions
+    }
+
+    /// Converts `self` to a map of the keys with their corresponding action.
+    pub fn into_actions(self) -> BTreeMap<Vec<u8>, SeqEntryAction> {
+        self.actions
+    }
+
+    /// Inserts a new key-value pair.
+    ///
+    /// Requires the new `version` of the sequenced entry content. If it does not match the current
+    /// version + 1, an error will be returned.
+    pub fn ins(mut self, key: Vec<u8>, content: Vec<u8>, version: u64) -> Self {
+        let _ = self.actions.insert(
+            key,
+            SeqEntryAction::Ins(SeqValue {
+                data: content,
+                version,
+            }),
+        );
+        self
+    }
+
+    /// Updates an existing key-value pair.
+    ///
+    /// Requires the new `version` of the sequenced entry content. If it does not match the current
+    /// version + 1, an error will be returned.
+    pub fn update(mut self, key: Vec<u8>, content: Vec<u8>, version: u64) -> Self {
+        let _ = self.actions.insert(
+            key,
+            SeqEntryAction::Update(SeqValue {
+                data: content,
+                version,
+            }),
+        );
+        self
+    }
+
+    /// Deletes an entry.
+    ///
+    /// Requires the new `version` of the sequenced entry content. If it does not match the current
+    /// version + 1, an error will be returned.
+    pub fn del(mut self, key: Vec<u8>, version: u64) -> Self {
+        let _ = self.actions.insert(key, SeqEntryAction::Del(version));
+        self
+    }
+
+    /// Adds an action to the list of actions, replacing it if it is already present.
+    pub fn add_action(&mut self, key: Vec<u8>, action: SeqEntryAction) {
+        let _ = self.actions.insert(key, action);
+    }
+}
+
+impl From<SeqEntryActions> for BTreeMap<Vec<u8>, SeqEntryAction> {
+    fn from(actions: SeqEntryActions) -> Self {
+        actions.actions
+    }
+}
+
+impl From<BTreeMap<Vec<u8>, SeqEntryAction>> for SeqEntryActions {
+    fn from(actions: BTreeMap<Vec<u8>, SeqEntryAction>) -> Self {
+        SeqEntryActions { actions }
+    }
+}
+
+/// Unsequenced Entry Actions for given entry keys.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug, Default)]
+pub struct UnseqEntryActions {
+    // A BTreeMap containing keys to which the corresponding unsequenced entry action is to be
+    // performed.
+    actions: BTreeMap<Vec<u8>, UnseqEntryAction>,
+}
+
+impl UnseqEntryActions {
+    /// Creates a new unsequenced Entry Actions list.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns a builder validating the actions as they are added.
+    pub fn builder() -> EntryActionsBuilder<UnseqEntryAction> {
+        Default::default()
+    }
+
+    /// Gets the actions.
+    pub fn actions(&self) -> &BTreeMap<Vec<u8>, UnseqEntryAction> {
+        &self.actions
+    }
+
+    /// Converts UnseqEntryActions struct to a BTreeMap of the keys with their corresponding action.
+    pub fn into_actions(self) -> BTreeMap<Vec<u8>, UnseqEntryAction> {
+        self.actions
+    }
+
+    /// Insert a new key-value pair
+    pub fn ins(mut self, key: Vec<u8>, content: Vec<u8>) -> Self {
+        let _ = self.actions.insert(key, UnseqEntryAction::Ins(content));
+        self
+    }
+
+    /// Update existing key-value pair
+    pub fn update(mut self, key: Vec<u8>, content: Vec<u8>) -> Self {
+        let _ = self.actions.insert(key, UnseqEntryAction::Update(content));
+        self
+    }
+
+    /// Delete existing key
+    pub fn del(mut self, key: Vec<u8>) -> Self {
+        let _ = self.actions.insert(key, UnseqEntryAction::Del);
+        self
+    }
+
+    /// Adds a UnseqEntryAction to the list of actions, replacing it if it is already present
+    pub fn add_action(&mut self, key: Vec<u8>, action: UnseqEntryAction) {
+        let _ = self.actions.insert(key, action);
+    }
+}
+
+impl From<UnseqEntryActions> for BTreeMap<Vec<u8>, UnseqEntryAction> {
+    fn from(actions: UnseqEntryActions) -> Self {
+        actions.actions
+    }
+}
+
+impl From<BTreeMap<Vec<u8>, UnseqEntryAction>> for UnseqEntryActions {
+    fn from(actions: BTreeMap<Vec<u8>, UnseqEntryAction>) -> Self {
+        UnseqEntryActions { actions }
+    }
+}
+
+/// Builder of entry actions which, unlike chaining on `SeqEntryActions` or
+/// `UnseqEntryActions`, rejects several actions on the same key instead of silently
+/// keeping the last one.
+///
+/// The first key given several actions is returned as `Error::DuplicateEntryAction`
+/// on build. Errors found checking the actions against the data are collected per key
+/// and returned as `Error::InvalidEntryActions`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EntryActionsBuilder<A> {
+    actions: BTreeMap<Vec<u8>, A>,
+    duplicate: Option<Vec<u8>>,
+    errors: BTreeMap<Vec<u8>, Error>,
+}
+
+impl<A> Default for EntryActionsBuilder<A> {
+    fn default() -> Self {
+        Self {
+            actions: BTreeMap::new(),
+            duplicate: None,
+            errors: BTreeMap::new(),
+        }
+    }
+}
+
+impl<A> EntryActionsBuilder<A> {
+    fn add(mut self, key: Vec<u8>, action: A) -> Self {
+        match self.actions.entry(key) {
+            Entry::Occupied(entry) => {
+                if self.duplicate.is_none() {
+                    self.duplicate = Some(entry.key().clone());
+                }
+            }
+            Entry::Vacant(entry) => {
+                let _ = entry.insert(action);
+            }
+        }
+        self
+    }
+
+    fn into_actions(self) -> Result<BTreeMap<Vec<u8>, A>> {
+        if let Some(key) = self.duplicate {
+            Err(Error::DuplicateEntryAction(key))
+        } else if self.errors.is_empty() {
+            Ok(self.actions)
+        } else {
+            Err(Error::InvalidEntryActions(self.errors))
+        }
+    }
+}
+
+impl EntryActionsBuilder<SeqEntryAction> {
+    /// Inserts a new key-value pair.
+    pub fn ins(self, key: Vec<u8>, content: Vec<u8>, version: u64) -> Self {
+        self.add(
+            key,
+            SeqEntryAction::Ins(SeqValue {
+                data: content,
+                version,
+            }),
+        )
+    }
+
+    /// Updates an existing key-value pair to the given new `version`.
+    pub fn update(self, key: Vec<u8>, content: Vec<u8>, version: u64) -> Self {
+        self.add(
+            key,
+            SeqEntryAction::Update(SeqValue {
+                data: content,
+                version,
+            }),
+        )
+    }
+
+    /// Deletes an entry at the given new `version`.
+    pub fn del(self, key: Vec<u8>, version: u64) -> Self {
+        self.add(key, SeqEntryAction::Del(version))
+    }
+
+    /// Builds the actions, failing if an action was added twice for the same key.
+    pub fn build(self) -> Result<SeqEntryActions> {
+        self.into_actions().map(SeqEntryActions::from)
+    }
+
+    /// Builds the actions, additionally checking them against the current state of the data
+    /// the same way `SeqData::mutate_entries` will: inserted keys must not exist, while
+    /// updated and deleted keys must exist and have their version incremented by one.
+    pub fn build_for(mut self, data: &SeqData) -> Result<SeqEntryActions> {
+        for (key, action) in &self.actions {
+            let error = match (action, data.get(key)) {
+                (SeqEntryAction::Ins(_), Some(current)) => {
+                    Some(Error::EntryExists(current.version as u8))
+                }
+                (SeqEntryAction::Ins(_), None) => None,
+                (_, None) => Some(Error::NoSuchEntry),
+                (action, Some(current)) if action.version() != current.version + 1 => {
+                    Some(Error::InvalidSuccessor(current.version))
+                }
+                _ => None,
+            };
+            if let Some(error) = error {
+                let _ = self.errors.entry(key.clone()).or_insert(error);
+            }
+        }
+        self.build()
+    }
+}
+
+impl EntryActionsBuilder<UnseqEntryAction> {
+    /// Inserts a new key-value pair.
+    pub fn ins(self, key: Vec<u8>, content: Vec<u8>) -> Self {
+        self.add(key, UnseqEntryAction::Ins(content))
+    }
+
+    /// Updates an existing key-value pair.
+    pub fn update(self, key: Vec<u8>, content: Vec<u8>) -> Self {
+        self.add(key, UnseqEntryAction::Update(content))
+    }
+
+    /// Deletes an existing key.
+    pub fn del(self, key: Vec<u8>) -> Self {
+        self.add(key, UnseqEntryAction::Del)
+    }
+
+    /// Builds the actions, failing if an action was added twice for the same key.
+    pub fn build(self) -> Result<UnseqEntryActions> {
+        self.into_actions().map(UnseqEntryActions::from)
+    }
+}
+
+/// Wrapper type for entry actions, which can be sequenced or unsequenced.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
+pub enum EntryActions {
+    /// Sequenced entry actions.
+    Seq(SeqEntryActions),
+    /// Unsequenced entry actions.
+    Unseq(UnseqEntryActions),
+}
+
+impl EntryActions {
+    /// Gets the kind.
+    pub fn kind(&self) -> Kind {
+        match self {
+            EntryActions::Seq(_) => Kind::Seq,
+            EntryActions::Unseq(_) => Kind::Unseq,
+        }
+    }
+}
+
+impl From<SeqEntryActions> for EntryActions {
+    fn from(entry_actions: SeqEntryActions) -> Self {
+        EntryActions::Seq(entry_actions)
+    }
+}
+
+impl From<UnseqEntryActions> for EntryActions {
+    fn from(entry_actions: UnseqEntryActions) -> Self {
+        EntryActions::Unseq(entry_actions)
+    }
+}
+
+/// Sequenced entries (key-value pairs, with versioned values).
+pub type SeqEntries = BTreeMap<Vec<u8>, SeqValue>;
+/// Unsequenced entries (key-value pairs, without versioned values).
+pub type UnseqEntries = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// Wrapper type for entries, which can be sequenced or unsequenced.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
+pub enum Entries {
+    /// Sequenced entries.
+    Seq(SeqEntries),
+    /// Unsequenced entries.
+    Unseq(UnseqEntries),
+}
+
+impl From<SeqEntries> for Entries {
+    fn from(entries: SeqEntries) -> Self {
+        Entries::Seq(entries)
+    }
+}
+
+impl From<UnseqEntries> for Entries {
+    fn from(entries: UnseqEntries) -> Self {
+        Entries::Unseq(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Action, Address, Data, PermissionChange, PermissionOp, PermissionSet, SeqData,
+        SeqEntryAction, SeqEntryActions, SeqValue, UnseqData, UnseqEntryActions, Value,
+        ValueHistory, XorName, MAX_MAP_SIZE_IN_BYTES,
+    };
+    use crate::{DeniedAction, Error, Keypair, PublicKey, Result, SignedWrite};
+    use rand::rngs::OsRng;
+    use std::collections::BTreeMap;
+    use threshold_crypto::SecretKey;
+
+    #[test]
+    fn zbase32_encode_decode_map_address() -> Result<()> {
+        let name = XorName(rand::random());
+        let address = Address::Seq { name, tag: 15000 };
+        let encoded = address.encode_to_zbase32()?;
+        let decoded = Address::decode_from_zbase32(&encoded)?;
+        assert_eq!(address, decoded);
+        Ok(())
+    }
+
+    #[test]
+    fn value_history() -> Result<()> {
+        let value = |data: &[u8], version| SeqValue {
+            data: data.to_vec(),
+            version,
+        };
+        let mut history = ValueHistory::new();
+        assert_eq!(
+            history.record(SeqEntryAction::Del(1)),
+            Err(Error::NoSuchEntry)
+        );
+
+        history.record(SeqEntryAction::Ins(value(b"a", 0)))?;
+        history.record(SeqEntryAction::Update(value(b"b", 1)))?;
+        assert_eq!(
+            history.record(SeqEntryAction::Update(value(b"c", 3))),
+            Err(Error::InvalidSuccessor(1))
+        );
+        history.record(SeqEntryAction::Del(2))?;
+        history.record(SeqEntryAction::Ins(value(b"d", 0)))?;
+
+        assert_eq!(history.entries().len(), 4);
+        assert!(history.entries()[2].deleted);
+        assert_eq!(
+            history.latest().map(|entry| &entry.data[..]),
+            Some(&b"d"[..])
+        );
+        assert_eq!(
+            history.at_version(1).map(|entry| &entry.data[..]),
+            Some(&b"b"[..])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn mutate_entries_with_history() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let mut data = SeqData::new(XorName::random(), 15000, owner);
+        let actions = SeqEntryActions::builder()
+            .ins(b"a".to_vec(), b"a0".to_vec(), 0)
+            .build()?;
+        data.mutate_entries(actions, &owner)?;
+
+        // The history of "a" starts from the value it had before it was recorded.
+        let mut histories = BTreeMap::new();
+        let actions = SeqEntryActions::builder()
+            .update(b"a".to_vec(), b"a1".to_vec(), 1)
+            .ins(b"b".to_vec(), b"b0".to_vec(), 0)
+            .build()?;
+        data.mutate_entries_with_history(actions, &owner, &mut histories)?;
+        let actions = SeqEntryActions::builder().del(b"a".to_vec(), 2).build()?;
+        data.mutate_entries_with_history(actions, &owner, &mut histories)?;
+
+        let a = &histories[&b"a".to_vec()];
+        assert_eq!(a.entries().len(), 3);
+        assert_eq!(
+            a.at_version(0).map(|entry| &entry.data[..]),
+            Some(&b"a0"[..])
+        );
+        assert_eq!(
+            a.at_version(1).map(|entry| &entry.data[..]),
+            Some(&b"a1"[..])
+        );
+        assert_eq!(a.latest().map(|entry| entry.deleted), Some(true));
+        assert_eq!(histories[&b"b".to_vec()].entries().len(), 1);
+
+        // A failed mutation records nothing.
+        let before = histories.clone();
+        let actions = SeqEntryActions::builder()
+            .update(b"b".to_vec(), b"b2".to_vec(), 2)
+            .build()?;
+        assert!(data
+            .mutate_entries_with_history(actions, &owner, &mut histories)
+            .is_err());
+        assert_eq!(histories, before);
+        Ok(())
+    }
+
+    #[test]
+    fn entry_actions_builder() -> Result<()> {
+        let actions = SeqEntryActions::builder()
+            .ins(b"a".to_vec(), b"a".to_vec(), 0)
+            .update(b"b".to_vec(), b"b".to_vec(), 1)
+            .del(b"c".to_vec(), 1)
+            .build()?;
+        assert_eq!(actions.actions().len(), 3);
+
+        let duplicate = UnseqEntryActions::builder()
+            .ins(b"a".to_vec(), b"a".to_vec())
+            .del(b"a".to_vec())
+            .update(b"b".to_vec(), b"b".to_vec())
+            .del(b"b".to_vec())
+            .build();
+        assert_eq!(duplicate, Err(Error::DuplicateEntryAction(b"a".to_vec())));
+
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let mut entries = BTreeMap::new();
+        let _ = entries.insert(
+            b"b".to_vec(),
+            SeqValue {
+                data: b"b".to_vec(),
+                version: 3,
+            },
+        );
+        let data =
+            SeqData::new_with_data(XorName::random(), 15000, entries, BTreeMap::new(), owner);
+        let result = SeqEntryActions::builder()
+            .ins(b"b".to_vec(), b"b".to_vec(), 0)
+            .build_for(&data);
+        let mut errors = BTreeMap::new();
+        let _ = errors.insert(b"b".to_vec(), Error::EntryExists(3));
+        assert_eq!(result, Err(Error::InvalidEntryActions(errors)));
+
+        let result = SeqEntryActions::builder()
+            .update(b"b".to_vec(), b"b".to_vec(), 5)
+            .del(b"c".to_vec(), 1)
+            .build_for(&data);
+        let mut errors = BTreeMap::new();
+        let _ = errors.insert(b"b".to_vec(), Error::InvalidSuccessor(3));
+        let _ = errors.insert(b"c".to_vec(), Error::NoSuchEntry);
+        assert_eq!(result, Err(Error::InvalidEntryActions(errors)));
+
+        let _ = SeqEntryActions::builder()
+            .update(b"b".to_vec(), b"b".to_vec(), 4)
+            .build_for(&data)?;
+        Ok(())
+    }
+
+    #[test]
+    fn access_denied_details() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let other = PublicKey::Bls(SecretKey::random().public_key());
+        let mut data = SeqData::new(XorName::random(), 15000, owner);
+        let actions = SeqEntryActions::builder()
+            .ins(b"a".to_vec(), b"a".to_vec(), 0)
+            .build()?;
+        let result = data.mutate_entries(actions, &other);
+        assert_eq!(
+            result,
+            Err(Error::AccessDenied {
+                requester: other,
+                action: DeniedAction::Map(Action::Insert),
+                data_address: Some((*data.address()).into()),
+            })
+        );
+        assert_eq!(result.unwrap_err().denied_requester(), Some(other));
+        Ok(())
+    }
+
+    #[test]
+    fn get_values() {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let mut entries = BTreeMap::new();
+        let _ = entries.insert(b"a".to_vec(), b"a".to_vec());
+        let data: Data =
+            UnseqData::new_with_data(XorName::random(), 15000, entries, BTreeMap::new(), owner)
+                .into();
+        let values = data.get_values(&[b"a".to_vec(), b"b".to_vec()]);
+        assert_eq!(values[&b"a".to_vec()], Ok(Value::Unseq(b"a".to_vec())));
+        assert_eq!(values[&b"b".to_vec()], Err(Error::NoSuchEntry));
+    }
+
+    #[test]
+    fn size_limit() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let mut data = UnseqData::new(XorName::random(), 15000, owner);
+        let half = vec![0; MAX_MAP_SIZE_IN_BYTES as usize / 2];
+
+        data.mutate_entries(
+            UnseqEntryActions::builder()
+                .ins(b"a".to_vec(), half.clone())
+                .build()?,
+            &owner,
+        )?;
+        assert!(data.validate_size());
+        assert!(data.entry_size(b"a") > Some(MAX_MAP_SIZE_IN_BYTES / 2));
+
+        let result = data.mutate_entries(
+            UnseqEntryActions::builder()
+                .ins(b"b".to_vec(), half)
+                .build()?,
+            &owner,
+        );
+        assert_eq!(result, Err(Error::ExceededSize));
+        assert_eq!(data.entry_size(b"b"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn permission_set_diff_and_merge() {
+        let read_insert = PermissionSet::new()
+            .allow(Action::Read)
+            .allow(Action::Insert);
+        let read_delete = PermissionSet::new()
+            .allow(Action::Read)
+            .allow(Action::Delete);
+
+        let delta = read_insert.diff(&read_delete);
+        assert!(delta.allow.contains(&Action::Delete));
+        assert!(delta.deny.contains(&Action::Insert));
+        assert_eq!(read_insert.clone().apply(&delta), read_delete);
+        assert!(read_insert.diff(&read_insert).is_empty());
+
+        let merged = read_insert.merge(&read_delete);
+        assert_eq!(merged, read_delete.merge(&read_insert));
+        assert!(merged.is_allowed(Action::Insert) && merged.is_allowed(Action::Delete));
+    }
+
+    #[test]
+    fn permission_history() -> Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let owner = owner_keypair.public_key();
+        let manager_keypair = Keypair::new_ed25519(&mut OsRng);
+        let manager = manager_keypair.public_key();
+        let mut data = SeqData::new(XorName::random(), 15000, owner);
+        let address = *data.address();
+        let op = |change, version| PermissionOp {
+            address,
+            change,
+            version,
+        };
+
+        let grant = PermissionChange::SetUserPermissions {
+            user: manager,
+            permissions: PermissionSet::new().allow(Action::ManagePermissions),
+        };
+        data.apply_permission_op(SignedWrite::new(op(grant, 1), &owner_keypair)?)?;
+
+        let revoke = PermissionChange::DelUserPermissions(manager);
+        data.apply_permission_op(SignedWrite::new(op(revoke, 2), &manager_keypair)?)?;
+
+        let take_over = PermissionChange::ChangeOwner(manager);
+        let result =
+            data.apply_permission_op(SignedWrite::new(op(take_over, 3), &manager_keypair)?);
+        assert!(matches!(result, Err(Error::AccessDenied { .. })));
+
+        let history = data.permission_history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].requester, owner);
+        assert_eq!(history[1].requester, manager);
+        assert_eq!(history[1].op.version, 2);
+        for change in history {
+            change.verify()?;
+        }
+        assert_eq!(data.shell().permission_history(), history);
+        Ok(())
+    }
+
+    #[test]
+    fn permission_op_size() -> Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let owner = owner_keypair.public_key();
+        let entries = |len| {
+            let mut entries = BTreeMap::new();
+            let value = SeqValue {
+                data: vec![0; len],
+                version: 0,
+            };
+            let _ = entries.insert(b"key".to_vec(), value);
+            entries
+        };
+        let name = XorName::random();
+        let empty = SeqData::new_with_data(name, 15000, entries(0), BTreeMap::new(), owner);
+        let len = (MAX_MAP_SIZE_IN_BYTES - empty.serialised_size()) as usize;
+        let mut data = SeqData::new_with_data(name, 15000, entries(len), BTreeMap::new(), owner);
+        assert_eq!(data.serialised_size(), MAX_MAP_SIZE_IN_BYTES);
+
+        let grant = PermissionOp {
+            address: *data.address(),
+            change: PermissionChange::SetUserPermissions {
+                user: Keypair::new_ed25519(&mut OsRng).public_key(),
+                permissions: PermissionSet::new().allow(Action::Read),
+            },
+            version: 1,
+        };
+        let request = SignedWrite::new(grant, &owner_keypair)?;
+        assert_eq!(
+            data.apply_permission_op(request.clone()),
+            Err(Error::ExceededSize)
+        );
+        assert_eq!(data.version(), 0);
+        assert!(data.permissions().is_empty());
+        assert!(data.permission_history().is_empty());
+        assert_eq!(data.serialised_size(), MAX_MAP_SIZE_IN_BYTES);
+
+        // Changes validated elsewhere are recorded too, once.
+        let mut data = empty;
+        assert!(data.apply_permission_op_without_validation(request.clone()));
+        assert!(!data.apply_permission_op_without_validation(request.clone()));
+        assert_eq!(data.version(), 1);
+        assert_eq!(data.permission_history(), &[request]);
+        Ok(())
+    }
+
+    #[test]
+    fn permission_op_without_validation() -> Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let owner = owner_keypair.public_key();
+        let mut data = SeqData::new(XorName::random(), 15000, owner);
+        let request = |address, version| {
+            let op = PermissionOp {
+                address,
+                change: PermissionChange::DelUserPermissions(owner),
+                version,
+            };
+            SignedWrite::new(op, &owner_keypair)
+        };
+        let address = *data.address();
+
+        // Neither a change skipping a version nor one to another Map is applied.
+        assert!(!data.apply_permission_op_without_validation(request(address, 2)?));
+        let other = Address::Seq {
+            name: XorName::random(),
+            tag: 15000,
+        };
+        assert!(!data.apply_permission_op_without_validation(request(other, 1)?));
+        assert_eq!(data.version(), 0);
+        assert!(data.permission_history().is_empty());
+
+        assert!(data.apply_permission_op_without_validation(request(address, 1)?));
+        assert!(data.apply_permission_op_without_validation(request(address, 2)?));
+        assert_eq!(data.version(), 2);
+        assert_eq!(data.permission_history().len(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{utils, Result, Scope, TypeTag};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+use xor_name::XorName;
+
+/// Set of Actions that can be performed on the Map.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    /// Permission to read entries.
+    Read,
+    /// Permission to insert new entries.
+    Insert,
+    /// Permission to update existing entries.
+    Update,
+    /// Permission to delete existing entries.
+    Delete,
+    /// Permission to modify permissions for other users.
+    ManagePermissions,
+}
+
+/// Kind of a Map.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum Kind {
+    /// Unsequenced.
+    Unseq,
+    /// Sequenced.
+    Seq,
+}
+
+impl Kind {
+    /// Creates `Kind` from a `sequenced` flag.
+    pub fn from_flag(sequenced: bool) -> Self {
+        if sequenced {
+            Kind::Seq
+        } else {
+            Kind::Unseq
+        }
+    }
+
+    /// Returns `true` if sequenced.
+    pub fn is_seq(self) -> bool {
+        self == Kind::Seq
+    }
+
+    /// Returns `true` if unsequenced.
+    pub fn is_unseq(self) -> bool {
+        !self.is_seq()
+    }
+}
+
+/// Address of an Map.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum Address {
+    /// Unsequenced namespace.
+    Unseq {
+        /// Name.
+        name: XorName,
+        /// Tag.
+        tag: u64,
+    },
+    /// Sequenced namespace.
+    Seq {
+        /// Name.
+        name: XorName,
+        /// Tag.
+        tag: u64,
+    },
+}
+
+impl Address {
+    /// Constructs an `Address` given `kind`, `name`, and `tag`.
+    pub fn from_kind(kind: Kind, name: XorName, tag: u64) -> Self {
+        match kind {
+            Kind::Seq => Address::Seq { name, tag },
+            Kind::Unseq => Address::Unseq { name, tag },
+        }
+    }
+
+    /// Constructs an `Address` given `kind`, `name`, and a checked `TypeTag`.
+    pub fn from_type_tag(kind: Kind, name: XorName, tag: TypeTag) -> Self {
+        Self::from_kind(kind, name, tag.value())
+    }
+
+    /// Returns the kind.
+    pub fn kind(&self) -> Kind {
+        match self {
+            Address::Seq { .. } => Kind::Seq,
+            Address::Unseq { .. } => Kind::Unseq,
+        }
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &XorName {
+        match self {
+            Address::Unseq { ref name, .. } | Address::Seq { ref name, .. } => name,
+        }
+    }
+
+    /// Returns the tag as a `TypeTag`.
+    pub fn type_tag(&self) -> TypeTag {
+        TypeTag(self.tag())
+    }
+
+    /// Returns the tag.
+    pub fn tag(&self) -> u64 {
+        match self {
+            Address::Unseq { tag, .. } | Address::Seq { tag, .. } => *tag,
+        }
+    }
+
+    /// Returns `true` if sequenced.
+    pub fn is_seq(&self) -> bool {
+        self.kind().is_seq()
+    }
+
+    /// Returns `true` if unsequenced.
+    pub fn is_unseq(&self) -> bool {
+        self.kind().is_unseq()
+    }
+
+    /// Returns the scope, which is always private for Map.
+    pub fn scope(&self) -> Scope {
+        Scope::Private
+    }
+
+    /// Returns the Address serialised and encoded in z-base-32.
+    pub fn encode_to_zbase32(&self) -> Result<String> {
+        utils::encode(&self)
+    }
+
+    /// Creates from z-base-32 encoded string.
+    pub fn decode_from_zbase32<T: AsRef<str>>(encoded: T) -> Result<Self> {
+        utils::decode(encoded)
+    }
+}
+
+impl Display for Address {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Address::Unseq { name, tag } => write!(formatter, "UnseqMap({}, {})", name, tag),
+            Address::Seq { name, tag } => write!(formatter, "SeqMap({}, {})", name, tag),
+        }
+    }
+}
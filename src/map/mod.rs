@@ -0,0 +1,41 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Map
+//!
+//! All Map is unpublished. Map can be either sequenced or unsequenced.
+//!
+//! ## Private data
+//!
+//! Please see `append_only_data.rs` for more about unpublished versus published data.
+//!
+//! ## Sequenced and unsequenced data.
+//!
+//! Explicitly sequencing all mutations is an option provided for clients to allow them to avoid
+//! dealing with conflicting mutations. However, we don't need the version for preventing replay
+//! attacks.
+//!
+//! For sequenced Map the client must specify the next version number of a value while
+//! modifying/deleting keys. Similarly, while modifying the Map shell (permissions,
+//! ownership, etc.), the next version number must be passed. For unsequenced Map the client
+//! does not have to pass version numbers for keys, but it still must pass the next version number
+//! while modifying the Map shell.
+
+#[cfg(feature = "map")]
+mod data;
+mod metadata;
+
+#[cfg(feature = "map")]
+pub use data::{
+    Data, Entries, EntryActions, EntryActionsBuilder, PermissionChange, PermissionDelta,
+    PermissionOp, PermissionSet, SeqData, SeqEntries, SeqEntryAction, SeqEntryActions, SeqValue,
+    UnseqData, UnseqEntries, UnseqEntryAction, UnseqEntryActions, Value, ValueHistory,
+    ValueHistoryEntry, Values, MAX_MAP_SIZE_IN_BYTES,
+};
+pub use metadata::{Action, Address, Kind};
@@ -0,0 +1,179 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Name resolution (NRS) records.
+//!
+//! A public name, e.g. `safenetwork` in `docs.safenetwork`, is a sequenced Map tagged
+//! `TypeTag::NRS_MAP` whose name is the SHA3-256 hash of `"nrs"` and the public name.
+//! Its content is a serialised `NrsMap`, resolving the public name and its sub names,
+//! e.g. `docs`, to the addresses of the data they point to.
+
+use crate::{utils, DataAddress, Error, MapAddress, MapKind, Result, TypeTag};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+use xor_name::XorName;
+
+const NRS_DOMAIN: &[u8] = b"nrs";
+
+/// A sub name of a public name, i.e. one of the labels before it.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub struct SubName(String);
+
+impl SubName {
+    /// Returns the sub name as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for SubName {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.0)
+    }
+}
+
+impl FromStr for SubName {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        if name.is_empty() || name.contains('.') || name.chars().any(char::is_whitespace) {
+            return Err(Error::FailedToParse(format!("Invalid sub name {:?}", name)));
+        }
+        Ok(Self(name.to_string()))
+    }
+}
+
+/// The target of a name: the address of some data, pinned to a version of it if given.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub struct NrsRecord {
+    /// Address of the data the name resolves to.
+    pub target: DataAddress,
+    /// Version of the data to resolve to, or `None` for the latest.
+    pub version: Option<u64>,
+}
+
+/// The records of a public name and, recursively, of its sub names.
+#[derive(Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct NrsMap {
+    /// Record the name itself resolves to, if any.
+    pub default: Option<NrsRecord>,
+    /// Records of the sub names.
+    pub sub_names: BTreeMap<SubName, NrsMap>,
+}
+
+impl NrsMap {
+    /// Returns the address of the Map holding the `NrsMap` of a public name.
+    pub fn address(public_name: &str) -> MapAddress {
+        let name = XorName::from_content(&[NRS_DOMAIN, public_name.as_bytes()]);
+        MapAddress::from_type_tag(MapKind::Seq, name, TypeTag::NRS_MAP)
+    }
+
+    /// Splits a name such as `docs.safenetwork` into its public name and its sub names,
+    /// the latter ordered from the public name outwards, e.g. `["docs"]`.
+    pub fn parse_name(name: &str) -> Result<(String, Vec<SubName>)> {
+        let mut labels = name.rsplit('.');
+        let public_name = match labels.next() {
+            Some(public_name) if !public_name.is_empty() => public_name.to_string(),
+            _ => return Err(Error::FailedToParse(format!("Invalid name {:?}", name))),
+        };
+        let sub_names = labels.map(SubName::from_str).collect::<Result<_>>()?;
+        Ok((public_name, sub_names))
+    }
+
+    /// Returns the record the given sub names resolve to, ordered from the public name
+    /// outwards, or `None` if there is none.
+    pub fn resolve(&self, sub_names: &[SubName]) -> Option<&NrsRecord> {
+        match sub_names.split_first() {
+            None => self.default.as_ref(),
+            Some((first, rest)) => self.sub_names.get(first)?.resolve(rest),
+        }
+    }
+
+    /// Sets the record of the given sub names, returning the one it replaces.
+    pub fn insert(&mut self, sub_names: &[SubName], record: NrsRecord) -> Option<NrsRecord> {
+        match sub_names.split_first() {
+            None => self.default.replace(record),
+            Some((first, rest)) => self
+                .sub_names
+                .entry(first.clone())
+                .or_default()
+                .insert(rest, record),
+        }
+    }
+
+    /// Removes the record of the given sub names, along with any sub names left empty,
+    /// returning the removed record.
+    pub fn remove(&mut self, sub_names: &[SubName]) -> Option<NrsRecord> {
+        match sub_names.split_first() {
+            None => self.default.take(),
+            Some((first, rest)) => {
+                let sub_map = self.sub_names.get_mut(first)?;
+                let record = sub_map.remove(rest);
+                if sub_map.is_empty() {
+                    let _ = self.sub_names.remove(first);
+                }
+                record
+            }
+        }
+    }
+
+    /// Returns true if the map holds no records.
+    pub fn is_empty(&self) -> bool {
+        self.default.is_none() && self.sub_names.is_empty()
+    }
+
+    /// Serialises the map, to be stored as the content of its Map.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        utils::serialise(self)
+    }
+
+    /// Deserialises a map stored with `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        utils::deserialise(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NrsMap, NrsRecord, SubName};
+    use crate::{BlobAddress, Result};
+    use std::str::FromStr;
+    use xor_name::XorName;
+
+    #[test]
+    fn resolve_sub_names() -> Result<()> {
+        let (public_name, sub_names) = NrsMap::parse_name("v1.docs.safenetwork")?;
+        assert_eq!(public_name, "safenetwork");
+        assert_eq!(
+            sub_names,
+            vec![SubName::from_str("docs")?, SubName::from_str("v1")?]
+        );
+        assert!(NrsMap::parse_name("a..safenetwork").is_err());
+
+        let record = NrsRecord {
+            target: BlobAddress::Public(XorName::random()).into(),
+            version: Some(1),
+        };
+        let mut map = NrsMap::default();
+        assert_eq!(map.insert(&sub_names, record), None);
+        assert_eq!(map.resolve(&sub_names), Some(&record));
+        assert_eq!(map.resolve(&sub_names[..1]), None);
+
+        let map = NrsMap::from_bytes(&map.to_bytes()?)?;
+        let mut map_copy = map.clone();
+        assert_eq!(map_copy.remove(&sub_names), Some(record));
+        assert!(map_copy.is_empty());
+        assert_eq!(map.resolve(&sub_names), Some(&record));
+        Ok(())
+    }
+}
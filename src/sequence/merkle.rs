@@ -0,0 +1,139 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Merkle tree over the entries of a Sequence, allowing to prove that an entry
+//! is part of it without holding all the entries.
+//!
+//! Leaves are the SHA3-256 hash of `0x00 || entry` and inner nodes the hash of
+//! `0x01 || left || right`. A node without a sibling is carried up to the next level as is.
+//! The root is the hash of `0x02 || len || top`, with the number of entries as big-endian
+//! `u64`, so that a proof can only be checked for the index and length it was made for.
+
+use super::metadata::Entry;
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Sha3};
+
+/// Hash of a Sequence entry, or of a node of the Merkle tree over the entries.
+pub type EntryHash = [u8; 32];
+
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+const ROOT_PREFIX: u8 = 2;
+
+/// Proof that an entry is included in a Sequence with a given Merkle root.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EntryProof {
+    /// Absolute index of the entry.
+    pub index: u64,
+    /// Number of entries of the Sequence.
+    pub len: u64,
+    /// Siblings on the path from the entry to the root, from the leaf up.
+    /// Whether each is on the left or the right follows from the index and length.
+    pub path: Vec<EntryHash>,
+}
+
+impl EntryProof {
+    /// Returns `true` if the entry is at `index` of the `len` entries under the given root.
+    pub fn verify(&self, entry: &[u8], root: &EntryHash) -> bool {
+        if self.index >= self.len {
+            return false;
+        }
+        let mut siblings = self.path.iter();
+        let mut position = self.index;
+        let mut width = self.len;
+        let mut hash = entry_hash(entry);
+        while width > 1 {
+            if position % 2 == 1 || position + 1 < width {
+                let sibling = match siblings.next() {
+                    Some(sibling) => sibling,
+                    None => return false,
+                };
+                hash = if position % 2 == 1 {
+                    node_hash(sibling, &hash)
+                } else {
+                    node_hash(&hash, sibling)
+                };
+            }
+            position /= 2;
+            width = width / 2 + width % 2;
+        }
+        siblings.next().is_none() && &root_hash(self.len, &hash) == root
+    }
+}
+
+/// Returns the hash of an entry, i.e. the leaf of the Merkle tree.
+pub fn entry_hash(entry: &[u8]) -> EntryHash {
+    hash(&[&[LEAF_PREFIX], entry])
+}
+
+/// Returns the Merkle root of the entries. The top of the tree of no entries is the hash of nothing.
+pub(crate) fn root<'a>(entries: impl Iterator<Item = &'a Entry>) -> EntryHash {
+    let mut level: Vec<EntryHash> = entries.map(|entry| entry_hash(entry)).collect();
+    let len = level.len() as u64;
+    if level.is_empty() {
+        return root_hash(len, &hash(&[]));
+    }
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    root_hash(len, &level[0])
+}
+
+/// Returns the inclusion proof of the entry at `index`, or `None` if out of bounds.
+pub(crate) fn proof<'a>(
+    entries: impl Iterator<Item = &'a Entry>,
+    index: u64,
+) -> Option<EntryProof> {
+    let mut level: Vec<EntryHash> = entries.map(|entry| entry_hash(entry)).collect();
+    let len = level.len() as u64;
+    let mut position = index as usize;
+    if position >= level.len() {
+        return None;
+    }
+    let mut path = Vec::new();
+    while level.len() > 1 {
+        if position % 2 == 1 {
+            path.push(level[position - 1]);
+        } else if let Some(sibling) = level.get(position + 1) {
+            path.push(*sibling);
+        }
+        level = next_level(&level);
+        position /= 2;
+    }
+    Some(EntryProof { index, len, path })
+}
+
+fn next_level(level: &[EntryHash]) -> Vec<EntryHash> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [single] => *single,
+            _ => unreachable!("chunks of at most two"),
+        })
+        .collect()
+}
+
+fn node_hash(left: &EntryHash, right: &EntryHash) -> EntryHash {
+    hash(&[&[NODE_PREFIX], left, right])
+}
+
+fn root_hash(len: u64, top: &EntryHash) -> EntryHash {
+    hash(&[&[ROOT_PREFIX], &len.to_be_bytes(), top])
+}
+
+fn hash(parts: &[&[u8]]) -> EntryHash {
+    let mut hasher = Sha3::v256();
+    let mut output = [0; 32];
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize(&mut output);
+    output
+}
@@ -7,12 +7,18 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use crate::{utils, Error, PublicKey, Result, XorName};
+#[cfg(feature = "sequence")]
+use crate::DeniedAction;
+use crate::{utils, Error, Keypair, PublicKey, Result, Scope, Signature, TypeTag, XorName};
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, fmt::Debug, hash::Hash};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Debug, Display, Formatter},
+    hash::Hash,
+};
 
 /// An action on Sequence data type.
-#[derive(Clone, Copy, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
 pub enum Action {
     /// Read from the data.
     Read,
@@ -54,6 +60,11 @@ impl Address {
         }
     }
 
+    /// Constructs an `Address` given `kind`, `name`, and a checked `TypeTag`.
+    pub fn from_type_tag(kind: Kind, name: XorName, tag: TypeTag) -> Self {
+        Self::from_kind(kind, name, tag.value())
+    }
+
     /// Returns the kind.
     pub fn kind(&self) -> Kind {
         match self {
@@ -62,6 +73,11 @@ impl Address {
         }
     }
 
+    /// Returns the scope.
+    pub fn scope(&self) -> Scope {
+        self.kind().into()
+    }
+
     /// Returns the name.
     pub fn name(&self) -> &XorName {
         match self {
@@ -69,6 +85,11 @@ impl Address {
         }
     }
 
+    /// Returns the tag as a `TypeTag`.
+    pub fn type_tag(&self) -> TypeTag {
+        TypeTag(self.tag())
+    }
+
     /// Returns the tag.
     pub fn tag(&self) -> u64 {
         match self {
@@ -97,6 +118,17 @@ impl Address {
     }
 }
 
+impl Display for Address {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Address::Public { name, tag } => write!(formatter, "PublicSequence({}, {})", name, tag),
+            Address::Private { name, tag } => {
+                write!(formatter, "PrivateSequence({}, {})", name, tag)
+            }
+        }
+    }
+}
+
 /// Kind of a Sequence.
 #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
 pub enum Kind {
@@ -118,6 +150,15 @@ impl Kind {
     }
 }
 
+impl From<Kind> for Scope {
+    fn from(kind: Kind) -> Self {
+        match kind {
+            Kind::Public => Scope::Public,
+            Kind::Private => Scope::Private,
+        }
+    }
+}
+
 /// Index of some data.
 #[derive(Copy, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Index {
@@ -127,6 +168,42 @@ pub enum Index {
     FromEnd(u64),
 }
 
+impl Index {
+    /// Index of the last entry.
+    pub fn last() -> Self {
+        Index::FromEnd(1)
+    }
+
+    /// Converts to an absolute index into data with `len` entries.
+    /// Returns `None` if the index is out of bounds, with `len` itself being in bounds
+    /// as the end of a range.
+    pub fn to_absolute(self, len: u64) -> Option<u64> {
+        match self {
+            Index::FromStart(index) if index <= len => Some(index),
+            Index::FromStart(_) => None,
+            Index::FromEnd(index) => len.checked_sub(index),
+        }
+    }
+
+    /// Moves the index `count` entries towards the end, keeping it relative to the same side.
+    /// Returns `None` on overflow, or if an end-relative index would move past the end.
+    pub fn checked_add(self, count: u64) -> Option<Self> {
+        match self {
+            Index::FromStart(index) => index.checked_add(count).map(Index::FromStart),
+            Index::FromEnd(index) => index.checked_sub(count).map(Index::FromEnd),
+        }
+    }
+
+    /// Moves the index `count` entries towards the start, keeping it relative to the same side.
+    /// Returns `None` on overflow, or if a start-relative index would move before the start.
+    pub fn checked_sub(self, count: u64) -> Option<Self> {
+        match self {
+            Index::FromStart(index) => index.checked_sub(count).map(Index::FromStart),
+            Index::FromEnd(index) => index.checked_add(count).map(Index::FromEnd),
+        }
+    }
+}
+
 impl From<u64> for Index {
     fn from(index: u64) -> Self {
         Index::FromStart(index)
@@ -204,6 +281,41 @@ pub enum User {
     Key(PublicKey),
 }
 
+/// Constraints on the entries of a Sequence, set in its policy. Unlimited by default.
+#[derive(
+    Copy, Clone, Default, Serialize, Deserialize, PartialEq, PartialOrd, Ord, Eq, Hash, Debug,
+)]
+pub struct Limits {
+    /// Maximum size in bytes of each entry.
+    pub max_entry_size: Option<u64>,
+    /// Maximum number of entries.
+    pub max_entries: Option<u64>,
+}
+
+impl Limits {
+    /// Returns `Err(EntryTooLarge)` or `Err(TooManyEntries)` if appending `entries`
+    /// to data already holding `len` entries would break the limits.
+    pub fn check_append<'a>(
+        &self,
+        len: u64,
+        entries: impl IntoIterator<Item = &'a Entry>,
+    ) -> Result<()> {
+        let mut count = 0;
+        for entry in entries {
+            if let Some(max) = self.max_entry_size {
+                if entry.len() as u64 > max {
+                    return Err(Error::EntryTooLarge(max));
+                }
+            }
+            count += 1;
+        }
+        match self.max_entries {
+            Some(max) if len.saturating_add(count) > max => Err(Error::TooManyEntries(max)),
+            _ => Ok(()),
+        }
+    }
+}
+
 /// Public permissions.
 #[derive(Clone, Serialize, Deserialize, PartialEq, PartialOrd, Ord, Eq, Hash, Debug)]
 pub struct PublicPolicy {
@@ -212,8 +324,12 @@ pub struct PublicPolicy {
     pub owner: PublicKey,
     /// Map of users to their public permission set.
     pub permissions: BTreeMap<User, PublicPermissions>,
+    /// Constraints on the entries.
+    #[serde(default)]
+    pub limits: Limits,
 }
 
+#[cfg(feature = "sequence")]
 impl PublicPolicy {
     /// Returns `Some(true)` if `action` is allowed for the provided user and `Some(false)` if it's
     /// not permitted. `None` means that default permissions should be applied.
@@ -232,8 +348,12 @@ pub struct PrivatePolicy {
     pub owner: PublicKey,
     /// Map of users to their private permission set.
     pub permissions: BTreeMap<PublicKey, PrivatePermissions>,
+    /// Constraints on the entries.
+    #[serde(default)]
+    pub limits: Limits,
 }
 
+#[cfg(feature = "sequence")]
 pub trait Perm {
     /// Returns true if `action` is allowed for the provided user.
     fn is_action_allowed(&self, requester: PublicKey, action: Action) -> Result<()>;
@@ -241,8 +361,11 @@ pub trait Perm {
     fn permissions(&self, user: User) -> Option<Permissions>;
     /// Returns the owner.
     fn owner(&self) -> &PublicKey;
+    /// Returns the constraints on the entries.
+    fn limits(&self) -> &Limits;
 }
 
+#[cfg(feature = "sequence")]
 impl Perm for PublicPolicy {
     /// Returns `Ok(())` if `action` is allowed for the provided user and `Err(AccessDenied)` if
     /// this action is not permitted.
@@ -256,8 +379,11 @@ impl Perm for PublicPolicy {
                 .or_else(|| self.is_action_allowed_by_user(&User::Anyone, action))
             {
                 Some(true) => Ok(()),
-                Some(false) => Err(Error::AccessDenied(requester)),
-                None => Err(Error::AccessDenied(requester)),
+                Some(false) | None => Err(Error::access_denied(
+                    requester,
+                    DeniedAction::Sequence(action),
+                    None,
+                )),
             }
         }
     }
@@ -271,8 +397,14 @@ impl Perm for PublicPolicy {
     fn owner(&self) -> &PublicKey {
         &self.owner
     }
+
+    /// Returns the constraints on the entries.
+    fn limits(&self) -> &Limits {
+        &self.limits
+    }
 }
 
+#[cfg(feature = "sequence")]
 impl Perm for PrivatePolicy {
     /// Returns `Ok(())` if `action` is allowed for the provided user and `Err(AccessDenied)` if
     /// this action is not permitted.
@@ -286,10 +418,18 @@ impl Perm for PrivatePolicy {
                     if perms.is_allowed(action) {
                         Ok(())
                     } else {
-                        Err(Error::AccessDenied(requester))
+                        Err(Error::access_denied(
+                            requester,
+                            DeniedAction::Sequence(action),
+                            None,
+                        ))
                     }
                 }
-                None => Err(Error::AccessDenied(requester)),
+                None => Err(Error::access_denied(
+                    requester,
+                    DeniedAction::Sequence(action),
+                    None,
+                )),
             }
         }
     }
@@ -306,6 +446,11 @@ impl Perm for PrivatePolicy {
     fn owner(&self) -> &PublicKey {
         &self.owner
     }
+
+    /// Returns the constraints on the entries.
+    fn limits(&self) -> &Limits {
+        &self.limits
+    }
 }
 
 /// Wrapper type for permissions, which can be public or private.
@@ -329,6 +474,54 @@ impl From<PublicPolicy> for Policy {
     }
 }
 
+impl Policy {
+    /// Returns the owner.
+    pub fn owner(&self) -> &PublicKey {
+        match self {
+            Policy::Public(policy) => &policy.owner,
+            Policy::Private(policy) => &policy.owner,
+        }
+    }
+}
+
+/// A policy for a Sequence, signed by the owner of the Sequence.
+///
+/// The signature covers the address of the Sequence along with the policy, so that
+/// a policy signed for one Sequence cannot be replayed on another.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct SignedPolicy {
+    /// Address of the Sequence the policy applies to.
+    pub address: Address,
+    /// The policy.
+    pub policy: Policy,
+    /// Signature of the owner over the address and policy.
+    pub signature: Signature,
+}
+
+impl SignedPolicy {
+    /// Signs the policy for the Sequence at `address` with the owner's keypair.
+    pub fn new(address: Address, policy: Policy, keypair: &Keypair) -> Result<Self> {
+        let bytes = utils::serialise(&(&address, &policy))?;
+        Ok(Self {
+            signature: keypair.sign(&bytes),
+            address,
+            policy,
+        })
+    }
+
+    /// Returns `Ok(())` if the policy was signed by `owner`, the owner of the Sequence
+    /// when the policy is applied, and `Err(Error::InvalidSignature)` otherwise.
+    pub fn verify(&self, owner: &PublicKey) -> Result<()> {
+        let bytes = utils::serialise(&(&self.address, &self.policy))?;
+        owner.verify(&self.signature, &bytes)
+    }
+
+    /// Returns the policy, dropping the signature.
+    pub fn into_policy(self) -> Policy {
+        self.policy
+    }
+}
+
 /// Wrapper type for permissions set, which can be public or private.
 #[derive(Clone, Serialize, Deserialize, PartialEq, PartialOrd, Ord, Eq, Hash, Debug)]
 pub enum Permissions {
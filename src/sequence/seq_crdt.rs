@@ -39,6 +39,20 @@ pub struct CrdtOperation<A: Actor + Display + Serialize, T> {
     pub signature: Option<Signature>,
 }
 
+/// CRDT Data operation appending several entries at once, applicable to other Sequence replica.
+/// A single signature covers all the entries.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CrdtBatchOperation<A: Actor + Display + Serialize, T> {
+    /// Address of a Sequence object on the network.
+    pub address: Address,
+    /// The data operations to apply, in order.
+    pub crdt_ops: Vec<Op<T, A>>,
+    /// The PublicKey of the entity that generated the operation
+    pub source: PublicKey,
+    /// The signature of source on the crdt_ops, required to apply the op
+    pub signature: Option<Signature>,
+}
+
 /// Sequence data type as a CRDT with Access Control
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd)]
 pub struct SequenceCrdt<A, P>
@@ -54,6 +68,9 @@ where
     data: LSeq<Entry, A>,
     /// The Policy matrix containing ownership and users permissions.
     policy: P,
+    /// Whether the data has been deleted, leaving only this tombstone.
+    #[serde(default)]
+    deleted: bool,
 }
 
 impl<A, P> Display for SequenceCrdt<A, P>
@@ -85,7 +102,28 @@ where
             address,
             data: LSeq::new_with_args(actor, LSEQ_TREE_BASE, LSEQ_BOUNDARY),
             policy,
+            deleted: false,
+        }
+    }
+
+    /// Constructs a new 'SequenceCrdt' already holding the given entries, in order.
+    ///
+    /// Returns `Err(EntryTooLarge)` or `Err(TooManyEntries)` if the entries break
+    /// the limits of the policy.
+    pub fn new_with_entries(
+        actor: A,
+        address: Address,
+        policy: P,
+        entries: Entries,
+    ) -> Result<Self> {
+        policy.limits().check_append(0, &entries)?;
+        let mut sequence = Self::new(actor, address, policy);
+        for entry in entries {
+            // Appending only generates the op, it has to be applied to insert the entry.
+            let op = sequence.data.append(entry);
+            sequence.data.apply(op);
         }
+        Ok(sequence)
     }
 
     /// Returns the address.
@@ -98,12 +136,31 @@ where
         self.data.len() as u64
     }
 
+    /// Drops all the entries, keeping the address and policy as a tombstone.
+    pub(crate) fn delete(&mut self) {
+        self.data = LSeq::new_with_args(self.actor.clone(), LSEQ_TREE_BASE, LSEQ_BOUNDARY);
+        self.deleted = true;
+    }
+
+    /// Returns true if the data has been deleted.
+    pub fn is_deleted(&self) -> bool {
+        self.deleted
+    }
+
+    /// Iterates over the entries, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &Entry> {
+        self.data.iter()
+    }
+
     /// Create crdt op to append a new item to the SequenceCrdt
     pub fn create_append_op(
         &mut self,
         entry: Entry,
         source: PublicKey,
     ) -> Result<CrdtOperation<A, Entry>> {
+        self.policy
+            .limits()
+            .check_append(self.len(), std::iter::once(&entry))?;
         let address = *self.address();
 
         // Append the entry to the LSeq
@@ -118,6 +175,62 @@ where
         })
     }
 
+    /// Create crdt op to append several new items to the SequenceCrdt
+    pub fn create_append_batch_op(
+        &self,
+        entries: Entries,
+        source: PublicKey,
+    ) -> Result<CrdtBatchOperation<A, Entry>> {
+        if entries.is_empty() {
+            return Err(Error::InvalidOperation);
+        }
+        self.policy.limits().check_append(self.len(), &entries)?;
+
+        let address = *self.address();
+
+        // Each append op is only positioned after the entries already in the LSeq, so every op
+        // needs to be applied before generating the next one. We do it on a copy so this replica
+        // is only mutated once the signed batch op is applied.
+        let mut data = self.data.clone();
+        let crdt_ops = entries
+            .into_iter()
+            .map(|entry| {
+                let crdt_op = data.append(entry);
+                data.apply(crdt_op.clone());
+                crdt_op
+            })
+            .collect();
+
+        Ok(CrdtBatchOperation {
+            address,
+            crdt_ops,
+            source,
+            signature: None,
+        })
+    }
+
+    /// Apply a remote batch of data CRDT operations to this replica of the Sequence.
+    pub fn apply_batch_op(&mut self, op: CrdtBatchOperation<A, Entry>) -> Result<()> {
+        // Note: Perms for the op are checked at the upper Sequence layer.
+        let sig = op.signature.ok_or(Error::CrdtMissingOpSignature)?;
+        let bytes_to_verify = utils::serialise(&op.crdt_ops).map_err(|err| {
+            Error::Serialisation(format!(
+                "Could not serialise CRDT batch operation to verify signature: {}",
+                err
+            ))
+        })?;
+        op.source.verify(&sig, &bytes_to_verify)?;
+        self.policy
+            .limits()
+            .check_append(self.len(), op.crdt_ops.iter().filter_map(inserted_entry))?;
+
+        for crdt_op in op.crdt_ops {
+            self.data.apply(crdt_op);
+        }
+
+        Ok(())
+    }
+
     /// Apply a remote data CRDT operation to this replica of the Sequence.
     pub fn apply_op(&mut self, op: CrdtOperation<A, Entry>) -> Result<()> {
         // Let's first check the op is validly signed.
@@ -131,6 +244,9 @@ where
             ))
         })?;
         op.source.verify(&sig, &bytes_to_verify)?;
+        self.policy
+            .limits()
+            .check_append(self.len(), inserted_entry(&op.crdt_op))?;
 
         // Apply the CRDT operation to the LSeq data
         self.data.apply(op.crdt_op);
@@ -180,9 +296,13 @@ where
 // Private helpers
 
 fn to_absolute_index(index: Index, count: usize) -> Option<usize> {
-    match index {
-        Index::FromStart(index) if (index as usize) <= count => Some(index as usize),
-        Index::FromStart(_) => None,
-        Index::FromEnd(index) => count.checked_sub(index as usize),
+    index.to_absolute(count as u64).map(|index| index as usize)
+}
+
+// Returns the entry inserted by the op, if any.
+fn inserted_entry<A: Actor>(op: &Op<Entry, A>) -> Option<&Entry> {
+    match op {
+        Op::Insert { val, .. } => Some(val),
+        Op::Delete { .. } => None,
     }
 }
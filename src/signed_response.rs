@@ -0,0 +1,137 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{utils, DataAddress, Error, PublicKey, Result, Signature};
+use serde::{Deserialize, Serialize};
+
+/// A query result endorsed by the section responsible for the data.
+///
+/// Clients can check the section signature instead of trusting whichever node replied.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct SignedResponse<T> {
+    /// The query result.
+    pub data: T,
+    /// Signature of the section over the serialised address and data.
+    pub section_sig: Signature,
+    /// The key of the section which signed the data.
+    pub section_key: PublicKey,
+    /// Address of the data queried, signed with the result so that the response
+    /// cannot be replayed for another query.
+    pub address: DataAddress,
+}
+
+impl<T: Serialize> SignedResponse<T> {
+    /// Returns the bytes the section signs for the result `data` of a query of `address`.
+    pub fn bytes_to_sign(address: &DataAddress, data: &T) -> Result<Vec<u8>> {
+        utils::serialise(&(address, data))
+    }
+
+    /// Returns `Ok(())` if the response answers a query of `address` and is signed by
+    /// `section_key`, which the caller must already trust, and
+    /// `Err(Error::InvalidSignature)` otherwise.
+    pub fn verify(&self, section_key: &PublicKey, address: &DataAddress) -> Result<()> {
+        if &self.section_key != section_key || &self.address != address {
+            return Err(Error::InvalidSignature);
+        }
+        let bytes = Self::bytes_to_sign(&self.address, &self.data)?;
+        self.section_key.verify(&self.section_sig, &bytes)
+    }
+
+    /// Returns the data, dropping the proof.
+    pub fn into_data(self) -> T {
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SignedResponse;
+    use crate::{BlobAddress, DataAddress, Error, PublicKey, Result, Signature, XorName};
+    use threshold_crypto::SecretKey;
+
+    fn signed_response(
+        address: DataAddress,
+        data: Vec<u8>,
+        secret_key: &SecretKey,
+    ) -> Result<SignedResponse<Vec<u8>>> {
+        let bytes = SignedResponse::bytes_to_sign(&address, &data)?;
+        Ok(SignedResponse {
+            data,
+            section_sig: Signature::Bls(secret_key.sign(&bytes)),
+            section_key: PublicKey::Bls(secret_key.public_key()),
+            address,
+        })
+    }
+
+    fn address() -> DataAddress {
+        BlobAddress::Public(XorName::random()).into()
+    }
+
+    #[test]
+    fn signed_response_verifies() -> Result<()> {
+        let secret_key = SecretKey::random();
+        let address = address();
+        let response = signed_response(address, b"some data".to_vec(), &secret_key)?;
+        response.verify(&PublicKey::Bls(secret_key.public_key()), &address)
+    }
+
+    #[test]
+    fn signed_response_fails_with_other_section_key() -> Result<()> {
+        let secret_key = SecretKey::random();
+        let section_key = PublicKey::Bls(secret_key.public_key());
+        let address = address();
+        let response = signed_response(address, b"some data".to_vec(), &secret_key)?;
+        let other_key = PublicKey::Bls(SecretKey::random().public_key());
+        assert_eq!(
+            response.verify(&other_key, &address),
+            Err(Error::InvalidSignature)
+        );
+
+        // A key minted by the responder signs for itself, but is not trusted.
+        let forger = SecretKey::random();
+        let forged = signed_response(address, b"other data".to_vec(), &forger)?;
+        assert_eq!(
+            forged.verify(&section_key, &address),
+            Err(Error::InvalidSignature)
+        );
+
+        let tampered = SignedResponse {
+            data: b"other data".to_vec(),
+            ..response
+        };
+        assert_eq!(
+            tampered.verify(&section_key, &address),
+            Err(Error::InvalidSignature)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn signed_response_fails_for_other_query() -> Result<()> {
+        let secret_key = SecretKey::random();
+        let section_key = PublicKey::Bls(secret_key.public_key());
+        let response = signed_response(address(), b"some data".to_vec(), &secret_key)?;
+        let other_address = address();
+        assert_eq!(
+            response.verify(&section_key, &other_address),
+            Err(Error::InvalidSignature)
+        );
+
+        // Relabelling the response breaks its signature.
+        let replayed = SignedResponse {
+            address: other_address,
+            ..response
+        };
+        assert_eq!(
+            replayed.verify(&section_key, &other_address),
+            Err(Error::InvalidSignature)
+        );
+        Ok(())
+    }
+}
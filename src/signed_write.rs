@@ -0,0 +1,273 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{utils, Keypair, PublicKey, Result, Signature};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, VecDeque},
+};
+use tiny_keccak::{Hasher, Sha3};
+
+/// Client-generated id of a write, shared by all the retries of the same write so that
+/// replicas can tell a retry from a new write.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub struct OpId(pub [u8; 32]);
+
+impl OpId {
+    /// Generates a random id, using the thread-local cryptographically secure RNG.
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+}
+
+/// A data write (e.g. a Blob to store, Map entry actions or a Sequence op) together with
+/// the signature of the client that authored it.
+///
+/// The signature covers the write op itself rather than the message carrying it, so the
+/// authorship can still be verified after the write has been forwarded by other nodes.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct SignedWrite<T> {
+    /// The write op.
+    pub op: T,
+    /// The key of the client which authored the write.
+    pub requester: PublicKey,
+    /// Signature of the requester over the serialised op and op id.
+    pub signature: Signature,
+    /// Id shared by the retries of this write, if the client set one.
+    #[serde(default)]
+    pub op_id: Option<OpId>,
+}
+
+impl<T: Serialize> SignedWrite<T> {
+    /// Signs the write op with the given keypair.
+    pub fn new(op: T, keypair: &Keypair) -> Result<Self> {
+        Self::new_with_op_id(op, None, keypair)
+    }
+
+    /// Signs the write op together with an id identifying its retries.
+    pub fn new_with_op_id(op: T, op_id: Option<OpId>, keypair: &Keypair) -> Result<Self> {
+        let bytes = utils::serialise(&(&op, &op_id))?;
+        Ok(Self {
+            signature: keypair.sign(&bytes),
+            requester: keypair.public_key(),
+            op_id,
+            op,
+        })
+    }
+
+    /// Returns `Ok(())` if the signature matches the op, op id and the requester,
+    /// and `Err(Error::InvalidSignature)` otherwise.
+    pub fn verify(&self) -> Result<()> {
+        let bytes = utils::serialise(&(&self.op, &self.op_id))?;
+        self.requester.verify(&self.signature, &bytes)
+    }
+
+    /// Returns true if both writes carry the same op id and the same op from the same
+    /// requester. A different op under the same op id is not a retry.
+    /// Writes without an op id are never retries of one another.
+    pub fn is_retry_of(&self, other: &Self) -> Result<bool> {
+        Ok(self.op_id.is_some()
+            && self.op_id == other.op_id
+            && self.requester == other.requester
+            && utils::serialise(&self.op)? == utils::serialise(&other.op)?)
+    }
+
+    /// Returns the op, dropping the signature.
+    pub fn into_op(self) -> T {
+        self.op
+    }
+}
+
+impl<T: Ord> Ord for SignedWrite<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.op, &self.requester, &self.op_id)
+            .cmp(&(&other.op, &other.requester, &other.op_id))
+            .then_with(|| signature_bytes(&self.signature).cmp(&signature_bytes(&other.signature)))
+    }
+}
+
+impl<T: Ord> PartialOrd for SignedWrite<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// The variant and bytes of a signature, which tell signatures apart exactly as their
+// equality does.
+fn signature_bytes(signature: &Signature) -> (u8, Vec<u8>) {
+    match signature {
+        Signature::Ed25519(signature) => (0, signature.to_bytes().to_vec()),
+        Signature::Bls(signature) => (1, signature.to_bytes().to_vec()),
+        Signature::BlsShare(share) => {
+            let mut bytes = (share.index as u64).to_be_bytes().to_vec();
+            bytes.extend_from_slice(&share.share.to_bytes());
+            (2, bytes)
+        }
+    }
+}
+
+/// The op ids of the most recently applied writes, used by replicas to acknowledge a
+/// retried write without applying it twice.
+#[derive(Clone, Debug)]
+pub struct AppliedOps {
+    capacity: usize,
+    ids: BTreeSet<AppliedOp>,
+    order: VecDeque<AppliedOp>,
+}
+
+// The requester, op id and hash of the serialised op of an applied write.
+type AppliedOp = (PublicKey, OpId, [u8; 32]);
+
+impl AppliedOps {
+    /// Creates a record remembering the given number of op ids, forgetting the oldest first.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ids: BTreeSet::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Records the op id of a write about to be applied. Returns false if the write
+    /// is a retry of one already applied, which must then not be applied again.
+    /// Writes without an op id are always new, as are writes reusing the op id of
+    /// another op, see `SignedWrite::is_retry_of`.
+    pub fn record<T: Serialize>(&mut self, write: &SignedWrite<T>) -> Result<bool> {
+        let op_id = match write.op_id {
+            Some(op_id) => op_id,
+            None => return Ok(true),
+        };
+        let mut hasher = Sha3::v256();
+        let mut op_hash = [0; 32];
+        hasher.update(&utils::serialise(&write.op)?);
+        hasher.finalize(&mut op_hash);
+        let id = (write.requester, op_id, op_hash);
+        if !self.ids.insert(id) {
+            return Ok(false);
+        }
+        self.order.push_back(id);
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                let _ = self.ids.remove(&oldest);
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AppliedOps, OpId, SignedWrite};
+    use crate::{Error, Keypair, Result, Signature};
+    use rand::rngs::OsRng;
+    use std::cmp::Ordering;
+    use threshold_crypto::SecretKey;
+
+    #[test]
+    fn signed_write_verifies() -> Result<()> {
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+        let write = SignedWrite::new(b"some data".to_vec(), &keypair)?;
+        write.verify()
+    }
+
+    #[test]
+    fn signed_write_fails_with_tampered_op() -> Result<()> {
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+        let write = SignedWrite::new(b"some data".to_vec(), &keypair)?;
+        let tampered = SignedWrite {
+            op: b"other data".to_vec(),
+            ..write
+        };
+        assert_eq!(tampered.verify(), Err(Error::InvalidSignature));
+        Ok(())
+    }
+
+    #[test]
+    fn retried_writes_are_recorded_once() -> Result<()> {
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+        let op_id = Some(OpId::random());
+        let write = SignedWrite::new_with_op_id(b"entry".to_vec(), op_id, &keypair)?;
+        let retry = SignedWrite::new_with_op_id(b"entry".to_vec(), op_id, &keypair)?;
+        write.verify()?;
+        assert!(retry.is_retry_of(&write)?);
+
+        let mut applied = AppliedOps::new(1);
+        assert!(applied.record(&write)?);
+        assert!(!applied.record(&retry)?);
+
+        let other = SignedWrite::new_with_op_id(b"entry".to_vec(), Some(OpId::random()), &keypair)?;
+        assert!(!other.is_retry_of(&write)?);
+        assert!(applied.record(&other)?);
+        // The capacity of one has dropped the first op id.
+        assert!(applied.record(&retry)?);
+        Ok(())
+    }
+
+    #[test]
+    fn other_op_under_same_op_id_is_not_a_retry() -> Result<()> {
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+        let op_id = Some(OpId::random());
+        let write = SignedWrite::new_with_op_id(b"entry".to_vec(), op_id, &keypair)?;
+        let other = SignedWrite::new_with_op_id(b"other entry".to_vec(), op_id, &keypair)?;
+        assert!(!other.is_retry_of(&write)?);
+
+        let mut applied = AppliedOps::new(2);
+        assert!(applied.record(&write)?);
+        assert!(applied.record(&other)?);
+        assert!(!applied.record(&other)?);
+
+        // Nor is the same op under the same op id from another requester.
+        let requester = Keypair::new_ed25519(&mut OsRng);
+        let foreign = SignedWrite::new_with_op_id(b"entry".to_vec(), op_id, &requester)?;
+        assert!(!foreign.is_retry_of(&write)?);
+        Ok(())
+    }
+
+    #[test]
+    fn op_id_is_signed() -> Result<()> {
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+        let write = SignedWrite::new_with_op_id(b"entry".to_vec(), Some(OpId::random()), &keypair)?;
+        let moved = SignedWrite {
+            op_id: Some(OpId::random()),
+            ..write.clone()
+        };
+        assert_eq!(moved.verify(), Err(Error::InvalidSignature));
+        let dropped = SignedWrite {
+            op_id: None,
+            ..write
+        };
+        assert_eq!(dropped.verify(), Err(Error::InvalidSignature));
+        Ok(())
+    }
+
+    #[test]
+    fn signed_write_order_matches_equality() -> Result<()> {
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+        let write = SignedWrite::new(b"entry".to_vec(), &keypair)?;
+        assert_eq!(write.cmp(&write.clone()), Ordering::Equal);
+
+        // Writes differing in any field, the signature included, are ordered apart.
+        let others = vec![
+            SignedWrite::new(b"other entry".to_vec(), &keypair)?,
+            SignedWrite::new(b"entry".to_vec(), &Keypair::new_ed25519(&mut OsRng))?,
+            SignedWrite::new_with_op_id(b"entry".to_vec(), Some(OpId::random()), &keypair)?,
+            SignedWrite {
+                signature: Signature::Bls(SecretKey::random().sign(b"entry")),
+                ..write.clone()
+            },
+        ];
+        for other in others {
+            assert_ne!(other, write);
+            assert_ne!(write.cmp(&other), Ordering::Equal);
+            assert_eq!(write.cmp(&other), other.cmp(&write).reverse());
+        }
+        Ok(())
+    }
+}
@@ -13,6 +13,7 @@ use std::{
     fmt::{self, Debug, Display, Formatter},
     str::FromStr,
 };
+use xor_name::XorName;
 
 /// The conversion from Token to raw value
 const TOKEN_TO_RAW_POWER_OF_10_CONVERSION: u32 = 9;
@@ -20,6 +21,9 @@ const TOKEN_TO_RAW_POWER_OF_10_CONVERSION: u32 = 9;
 /// The conversion from Token to raw value
 const TOKEN_TO_RAW_CONVERSION: u64 = 1_000_000_000;
 
+/// Total supply of the native token, all of it credited at genesis: 2^32 - 1 whole tokens.
+pub const GENESIS_SUPPLY: Token = Token::from_nano(u32::MAX as u64 * TOKEN_TO_RAW_CONVERSION);
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 /// Structure representing a Token amount.
 pub struct Token(u64);
@@ -102,6 +106,170 @@ impl Display for Token {
     }
 }
 
+/// Identifies the kind of token an amount is denominated in.
+///
+/// All transfers use the native network token unless they state otherwise,
+/// leaving room for application-level or test tokens.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Debug)]
+pub struct TokenId(pub XorName);
+
+impl TokenId {
+    /// The native network token.
+    pub const NATIVE: TokenId = TokenId(XorName([0; 32]));
+
+    /// Returns `true` if this is the native network token.
+    pub fn is_native(&self) -> bool {
+        *self == Self::NATIVE
+    }
+}
+
+impl Default for TokenId {
+    fn default() -> Self {
+        Self::NATIVE
+    }
+}
+
+/// Unit in which a `TokenFormatter` renders and parses amounts.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Debug)]
+pub enum TokenUnit {
+    /// 10^-9 of a whole token, i.e. the raw value.
+    Nano,
+    /// 10^-6 of a whole token.
+    Micro,
+    /// 10^-3 of a whole token.
+    Milli,
+    /// A whole token.
+    Whole,
+}
+
+impl TokenUnit {
+    /// Number of decimal places between this unit and nano tokens.
+    pub const fn exponent(self) -> u32 {
+        match self {
+            Self::Nano => 0,
+            Self::Micro => 3,
+            Self::Milli => 6,
+            Self::Whole => TOKEN_TO_RAW_POWER_OF_10_CONVERSION,
+        }
+    }
+}
+
+/// How to round amounts which cannot be represented exactly.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Debug)]
+pub enum RoundingMode {
+    /// Round towards zero.
+    Down,
+    /// Round away from zero.
+    Up,
+    /// Round to the nearest value, with halves rounded away from zero.
+    HalfUp,
+}
+
+impl RoundingMode {
+    // Rounds `quotient + remainder / divisor`.
+    fn apply(self, quotient: u128, remainder: u128, divisor: u128) -> u128 {
+        let round_up = match self {
+            Self::Down => false,
+            Self::Up => remainder > 0,
+            Self::HalfUp => remainder * 2 >= divisor,
+        };
+        if round_up {
+            quotient + 1
+        } else {
+            quotient
+        }
+    }
+}
+
+/// Locale-independent fixed-point formatting and parsing of Token amounts,
+/// working on integers only so no precision is lost to floats.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct TokenFormatter {
+    unit: TokenUnit,
+    decimals: u32,
+    rounding: RoundingMode,
+}
+
+impl TokenFormatter {
+    /// Constructs a formatter rendering amounts in `unit` with `decimals` decimal places,
+    /// rounding with `rounding` where the amount has more precision than that.
+    pub fn new(unit: TokenUnit, decimals: u32, rounding: RoundingMode) -> Self {
+        Self {
+            unit,
+            decimals,
+            rounding,
+        }
+    }
+
+    /// Renders the amount, e.g. `"1.50"` for 1.5 whole tokens with 2 decimals.
+    /// Nano tokens have no fractional part, so are rendered without decimals.
+    pub fn format(&self, amount: Token) -> String {
+        let exponent = self.unit.exponent();
+        let exact_decimals = self.decimals.min(exponent);
+        let divisor = 10_u128.pow(exponent - exact_decimals);
+        let nano = u128::from(amount.as_nano());
+        let scaled = self.rounding.apply(nano / divisor, nano % divisor, divisor);
+
+        let decimals_divisor = 10_u128.pow(exact_decimals);
+        let units = scaled / decimals_divisor;
+        if exact_decimals == 0 {
+            return units.to_string();
+        }
+        format!(
+            "{}.{:0width$}{}",
+            units,
+            scaled % decimals_divisor,
+            "0".repeat((self.decimals - exact_decimals) as usize),
+            width = exact_decimals as usize
+        )
+    }
+
+    /// Parses an amount expressed in the formatter's unit, rounding any precision
+    /// finer than a nano token with the formatter's rounding mode.
+    pub fn parse(&self, value_str: &str) -> Result<Token> {
+        let parse_error =
+            || Error::FailedToParse(format!("Can't parse token amount {}", value_str));
+        let mut itr = value_str.splitn(2, '.');
+        let units_str = itr.next().unwrap_or_default();
+        let fraction_str = itr.next().unwrap_or_default();
+        let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+        if units_str.is_empty() || !is_digits(units_str) || !is_digits(fraction_str) {
+            return Err(parse_error());
+        }
+
+        let exponent = self.unit.exponent() as usize;
+        let units = units_str
+            .parse::<u128>()
+            .map_err(|_| Error::ExcessiveValue)?;
+        let (exact, extra) = fraction_str.split_at(fraction_str.len().min(exponent));
+        let fraction = if exact.is_empty() {
+            0
+        } else {
+            exact.parse::<u128>().map_err(|_| parse_error())?
+                * 10_u128.pow((exponent - exact.len()) as u32)
+        };
+        let (remainder, divisor) = match extra.chars().next() {
+            // Only the first extra digit and whether any other is non-zero matter for rounding.
+            Some(first) => {
+                let first = u128::from(first.to_digit(10).unwrap_or_default());
+                let rest = u128::from(extra[1..].chars().any(|c| c != '0'));
+                (first * 10 + rest, 100)
+            }
+            None => (0, 1),
+        };
+
+        let nano = units
+            .checked_mul(10_u128.pow(exponent as u32))
+            .and_then(|nano| nano.checked_add(fraction))
+            .map(|nano| self.rounding.apply(nano, remainder, divisor))
+            .ok_or(Error::ExcessiveValue)?;
+        if nano > u128::from(u64::MAX) {
+            return Err(Error::ExcessiveValue);
+        }
+        Ok(Token::from_nano(nano as u64))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -176,4 +344,56 @@ mod tests {
         assert_eq!(None, Token(0).checked_sub(Token(u64::MAX)));
         assert_eq!(None, Token(10).checked_sub(Token(11)));
     }
+
+    #[test]
+    fn formatter() -> Result<()> {
+        let amount = Token(1_234_567_891);
+        let whole = |decimals, rounding| TokenFormatter::new(TokenUnit::Whole, decimals, rounding);
+        assert_eq!("1.23", whole(2, RoundingMode::Down).format(amount));
+        assert_eq!("1.24", whole(2, RoundingMode::Up).format(amount));
+        assert_eq!("1.23", whole(2, RoundingMode::HalfUp).format(amount));
+        assert_eq!("1", whole(0, RoundingMode::HalfUp).format(amount));
+        assert_eq!(
+            "1.23456789100",
+            whole(11, RoundingMode::Down).format(amount)
+        );
+        assert_eq!(
+            "1234567.891",
+            TokenFormatter::new(TokenUnit::Micro, 3, RoundingMode::Down).format(amount)
+        );
+        assert_eq!(
+            "1234567891",
+            TokenFormatter::new(TokenUnit::Nano, 0, RoundingMode::Down).format(amount)
+        );
+        assert_eq!(
+            "1234567891",
+            TokenFormatter::new(TokenUnit::Nano, 3, RoundingMode::Up).format(amount)
+        );
+        assert_eq!(
+            amount,
+            TokenFormatter::new(TokenUnit::Nano, 3, RoundingMode::Down).parse("1234567891")?
+        );
+
+        assert_eq!(amount, whole(2, RoundingMode::Down).parse("1.234567891")?);
+        assert_eq!(Token(1), whole(2, RoundingMode::Up).parse("0.0000000001")?);
+        assert_eq!(
+            Token(0),
+            whole(2, RoundingMode::HalfUp).parse("0.0000000004")?
+        );
+        assert_eq!(
+            Token(1),
+            whole(2, RoundingMode::HalfUp).parse("0.0000000005")?
+        );
+        assert_eq!(
+            Token(1_500),
+            TokenFormatter::new(TokenUnit::Micro, 0, RoundingMode::Down).parse("1.5")?
+        );
+        assert!(whole(2, RoundingMode::Down).parse("-1").is_err());
+        assert!(whole(2, RoundingMode::Down).parse(".5").is_err());
+        assert_eq!(
+            Err(Error::ExcessiveValue),
+            whole(2, RoundingMode::Down).parse("18446744074")
+        );
+        Ok(())
+    }
 }
@@ -8,13 +8,20 @@
 // Software.
 
 use super::{
-    keys::{PublicKey, Signature, SignatureShare},
-    token::Token,
+    clock::{Clock, SystemClock},
+    keys::{Keypair, PublicKey, QuorumConfig, Signature, SignatureShare},
+    signed_write::SignedWrite,
+    token::{Token, TokenId, GENESIS_SUPPLY},
     utils, Error, Result,
 };
 use crdts::Dot;
 use serde::{Deserialize, Serialize};
-use std::fmt::{self, Debug, Display, Formatter};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Debug, Display, Formatter},
+    ops::Deref,
+    sync::Arc,
+};
 use threshold_crypto::PublicKeySet;
 use tiny_keccak::{Hasher, Sha3};
 
@@ -57,6 +64,13 @@ pub struct Transfer {
     pub debit_id: DebitId,
     /// Msg, containing any data to the recipient.
     pub msg: Msg,
+    /// The token to transfer, the native token unless stated otherwise.
+    #[serde(default)]
+    pub token: TokenId,
+    /// Earliest time, in seconds since the UNIX epoch, at which the transfer
+    /// may be registered. Zero for no time-lock.
+    #[serde(default)]
+    pub not_before: u64,
 }
 
 impl Transfer {
@@ -65,6 +79,8 @@ impl Transfer {
         Debit {
             id: self.debit_id,
             amount: self.amount,
+            token: self.token,
+            not_before: self.not_before,
         }
     }
 
@@ -73,10 +89,42 @@ impl Transfer {
         Ok(Credit {
             id: self.debit().credit_id()?,
             amount: self.amount,
+            token: self.token,
             recipient: self.to,
             msg: self.msg.to_string(),
         })
     }
+
+    /// Signs the debit and credit with the sender's keypair.
+    ///
+    /// Returns `Err(SigningKeyTypeMismatch)` or `Err(InvalidSignature)` if the keypair
+    /// does not sign for the sender. BLS share keypairs sign their shares through
+    /// `SignedDebitShare` and `SignedCreditShare` instead.
+    pub fn sign(&self, keypair: &Keypair) -> Result<SignedTransfer> {
+        self.sign_with(|bytes| keypair.sign(bytes))
+    }
+
+    /// Signs the debit and credit with the sender's BLS secret key.
+    pub fn sign_bls(&self, secret_key: &threshold_crypto::SecretKey) -> Result<SignedTransfer> {
+        self.sign_with(|bytes| Signature::Bls(secret_key.sign(bytes)))
+    }
+
+    fn sign_with(&self, sign: impl Fn(&[u8]) -> Signature) -> Result<SignedTransfer> {
+        let debit = self.debit();
+        let credit = self.credit()?;
+        let transfer = SignedTransfer {
+            debit: SignedDebit {
+                actor_signature: sign(&utils::serialise(&debit)?),
+                debit,
+            },
+            credit: SignedCredit {
+                actor_signature: sign(&utils::serialise(&credit)?),
+                credit,
+            },
+        };
+        transfer.verify()?;
+        Ok(transfer)
+    }
 }
 
 /// A debit of tokens at a key.
@@ -86,6 +134,13 @@ pub struct Debit {
     pub id: DebitId,
     /// The amount to debit.
     pub amount: Token,
+    /// The token to debit.
+    #[serde(default)]
+    pub token: TokenId,
+    /// Earliest time, in seconds since the UNIX epoch, at which the debit
+    /// may be registered. Zero for no time-lock.
+    #[serde(default)]
+    pub not_before: u64,
 }
 
 impl Debit {
@@ -99,11 +154,31 @@ impl Debit {
         self.amount
     }
 
+    /// Get the token of this debit
+    pub fn token(&self) -> TokenId {
+        self.token
+    }
+
     /// Get the key to be debited
     pub fn sender(&self) -> PublicKey {
         self.id.actor
     }
 
+    /// Get the time before which this debit may not be registered
+    pub fn not_before(&self) -> u64 {
+        self.not_before
+    }
+
+    /// Returns `Error::TransferTimeLocked` if the debit may not be registered at `now`,
+    /// in seconds since the UNIX epoch.
+    pub fn check_not_before(&self, now: u64) -> Result<()> {
+        if now < self.not_before {
+            Err(Error::TransferTimeLocked(self.not_before))
+        } else {
+            Ok(())
+        }
+    }
+
     ///
     pub fn credit_id(&self) -> Result<CreditId> {
         let id_bytes = &utils::serialise(&self.id)?;
@@ -126,6 +201,9 @@ pub struct Credit {
     pub recipient: PublicKey,
     /// Msg, containing any data to the recipient.
     pub msg: Msg,
+    /// The token to credit.
+    #[serde(default)]
+    pub token: TokenId,
 }
 
 impl Credit {
@@ -139,6 +217,11 @@ impl Credit {
         self.amount
     }
 
+    /// Get the token of this credit
+    pub fn token(&self) -> TokenId {
+        self.token
+    }
+
     /// Get the key to be credited
     pub fn recipient(&self) -> PublicKey {
         self.recipient
@@ -214,6 +297,11 @@ impl CreditAgreementProof {
         self.signed_credit.amount()
     }
 
+    /// Get the token of this credit
+    pub fn token(&self) -> TokenId {
+        self.signed_credit.token()
+    }
+
     /// Get the recipient of this credit
     pub fn recipient(&self) -> PublicKey {
         self.signed_credit.recipient()
@@ -251,6 +339,11 @@ impl TransferAgreementProof {
         self.signed_debit.amount()
     }
 
+    /// Get the token of this transfer
+    pub fn token(&self) -> TokenId {
+        self.signed_debit.token()
+    }
+
     /// Get the sender of this transfer
     pub fn sender(&self) -> PublicKey {
         self.signed_debit.sender()
@@ -266,6 +359,17 @@ impl TransferAgreementProof {
         self.debiting_replicas_keys.clone()
     }
 
+    /// Get the time before which this transfer may not be registered
+    pub fn not_before(&self) -> u64 {
+        self.signed_debit.not_before()
+    }
+
+    /// Returns `Error::TransferTimeLocked` if the transfer may not be registered at `now`,
+    /// in seconds since the UNIX epoch. Replicas must check this before registering.
+    pub fn check_not_before(&self, now: u64) -> Result<()> {
+        self.signed_debit.debit.check_not_before(now)
+    }
+
     /// Get the corresponding credit agreement proof.
     pub fn credit_proof(&self) -> CreditAgreementProof {
         CreditAgreementProof {
@@ -310,6 +414,11 @@ impl SignedTransfer {
         self.debit.amount()
     }
 
+    /// Get the token of this transfer
+    pub fn token(&self) -> TokenId {
+        self.debit.token()
+    }
+
     /// Get the sender of this transfer
     pub fn sender(&self) -> PublicKey {
         self.debit.id().actor
@@ -319,6 +428,19 @@ impl SignedTransfer {
     pub fn credit_id(&self) -> Result<CreditId> {
         self.debit.credit_id()
     }
+
+    /// Returns `Ok(())` if the debit and credit match and are both signed by the sender,
+    /// whether with an Ed25519 or a BLS key.
+    pub fn verify(&self) -> Result<()> {
+        if self.debit.amount() != self.credit.amount()
+            || self.debit.token() != self.credit.token()
+            || self.debit.credit_id()? != *self.credit.id()
+        {
+            return Err(Error::InvalidOperation);
+        }
+        self.debit.verify()?;
+        self.credit.verify(&self.sender())
+    }
 }
 
 /// An Actor cmd.
@@ -341,6 +463,11 @@ impl SignedDebit {
         self.debit.amount()
     }
 
+    /// Get the token of this transfer
+    pub fn token(&self) -> TokenId {
+        self.debit.token()
+    }
+
     /// Get the sender of this transfer
     pub fn sender(&self) -> PublicKey {
         self.debit.sender()
@@ -351,6 +478,18 @@ impl SignedDebit {
         self.debit.credit_id()
     }
 
+    /// Get the time before which this transfer may not be registered
+    pub fn not_before(&self) -> u64 {
+        self.debit.not_before()
+    }
+
+    /// Returns `Ok(())` if the debit is signed by its sender, and
+    /// `Err(SigningKeyTypeMismatch)` if the signature is of another scheme than the key.
+    pub fn verify(&self) -> Result<()> {
+        self.sender()
+            .verify(&self.actor_signature, &utils::serialise(&self.debit)?)
+    }
+
     /// Tries to represent the signed debit as a share.
     pub fn as_share(&self) -> Result<SignedDebitShare> {
         if let Signature::BlsShare(share) = self.actor_signature.clone() {
@@ -384,11 +523,22 @@ impl SignedCredit {
         self.credit.amount
     }
 
+    /// Get the token of this transfer
+    pub fn token(&self) -> TokenId {
+        self.credit.token
+    }
+
     /// Get the sender of this transfer
     pub fn recipient(&self) -> PublicKey {
         self.credit.recipient()
     }
 
+    /// Returns `Ok(())` if the credit is signed by `sender`, and
+    /// `Err(SigningKeyTypeMismatch)` if the signature is of another scheme than the key.
+    pub fn verify(&self, sender: &PublicKey) -> Result<()> {
+        sender.verify(&self.actor_signature, &utils::serialise(&self.credit)?)
+    }
+
     /// Tries to represent the signed credit as a share.
     pub fn as_share(&self) -> Result<SignedCreditShare> {
         if let Signature::BlsShare(share) = self.actor_signature.clone() {
@@ -427,6 +577,9 @@ impl SignedTransferShare {
         if debit.amount() != credit.amount() {
             return Err(Error::InvalidOperation);
         }
+        if debit.token() != credit.token() {
+            return Err(Error::InvalidOperation);
+        }
         if debit.credit_id()? != *credit.id() {
             return Err(Error::InvalidOperation);
         }
@@ -452,6 +605,11 @@ impl SignedTransferShare {
         self.debit.amount()
     }
 
+    /// Get the token of this transfer
+    pub fn token(&self) -> TokenId {
+        self.debit.token()
+    }
+
     /// Get the sender of this transfer
     pub fn sender(&self) -> PublicKey {
         self.debit.id().actor
@@ -481,6 +639,11 @@ impl SignedTransferShare {
     pub fn actors(&self) -> &PublicKeySet {
         &self.actors
     }
+
+    /// Get the quorum rules of the actors.
+    pub fn actors_quorum(&self) -> QuorumConfig {
+        QuorumConfig::from(&self.actors)
+    }
 }
 
 impl Debug for SignedTransferShare {
@@ -515,6 +678,11 @@ impl SignedDebitShare {
         self.debit.amount()
     }
 
+    /// Get the token of this transfer
+    pub fn token(&self) -> TokenId {
+        self.debit.token()
+    }
+
     /// Get the sender of this transfer
     pub fn sender(&self) -> PublicKey {
         self.debit.sender()
@@ -551,6 +719,11 @@ impl SignedCreditShare {
         self.credit.amount
     }
 
+    /// Get the token of this transfer
+    pub fn token(&self) -> TokenId {
+        self.credit.token
+    }
+
     /// Get the sender of this transfer
     pub fn recipient(&self) -> PublicKey {
         self.credit.recipient()
@@ -582,6 +755,473 @@ pub enum ReplicaEvent {
     /// The event raised when
     /// PropagateTransfer cmd has been successful.
     TransferPropagated(TransferPropagated),
+    /// The event raised when
+    /// a failed store payment has been refunded to its sender.
+    TransferRefunded(TransferRefunded),
+    /// The event raised when
+    /// the genesis supply has been credited.
+    GenesisCredited(GenesisProof),
+    /// The event raised when
+    /// CancelValidation cmd has been successful.
+    TransferValidationCancelled(TransferValidationCancelled),
+}
+
+/// Returns the balance of `key` in `token` after applying the first `version` events,
+/// where debits are applied on registration and credits on propagation.
+/// Returns `Error::InvalidOperation` if there are fewer than `version` events,
+/// or if the events would take the balance below zero.
+pub fn balance_at(
+    events: &[ReplicaEvent],
+    key: &PublicKey,
+    token: TokenId,
+    version: usize,
+) -> Result<Token> {
+    if version > events.len() {
+        return Err(Error::InvalidOperation);
+    }
+    events
+        .iter()
+        .take(version)
+        .try_fold(Token::zero(), |balance, event| match event {
+            ReplicaEvent::TransferRegistered(event)
+                if &event.sender() == key && event.token() == token =>
+            {
+                balance
+                    .checked_sub(event.amount())
+                    .ok_or(Error::InvalidOperation)
+            }
+            ReplicaEvent::TransferPropagated(event)
+                if &event.recipient() == key && event.token() == token =>
+            {
+                balance
+                    .checked_add(event.amount())
+                    .ok_or(Error::ExcessiveValue)
+            }
+            ReplicaEvent::TransferRefunded(event)
+                if &event.recipient() == key && event.token() == token =>
+            {
+                balance
+                    .checked_add(event.amount())
+                    .ok_or(Error::ExcessiveValue)
+            }
+            ReplicaEvent::GenesisCredited(event)
+                if &event.recipient() == key && event.token() == token =>
+            {
+                balance
+                    .checked_add(event.amount())
+                    .ok_or(Error::ExcessiveValue)
+            }
+            _ => Ok(balance),
+        })
+}
+
+impl ReplicaEvent {
+    /// Verifies the signatures carried by the event.
+    ///
+    /// Validated and registered transfers must have been signed by the given Replicas.
+    /// Credits must have been signed by the given Replicas or one of the `known_groups`,
+    /// so that a proof signed by keys it carries itself is not accepted.
+    /// Proposals are checked with `TransferValidationProposed::verify`, and
+    /// cancellations against the Actor signature they carry.
+    pub fn verify(
+        &self,
+        replicas: &ReplicaPublicKeySet,
+        known_groups: &BTreeSet<threshold_crypto::PublicKey>,
+    ) -> Result<()> {
+        let verify_credit_proof = |proof: &CreditAgreementProof| {
+            let group = proof.debiting_replicas_keys.public_key();
+            if group != replicas.public_key() && !known_groups.contains(&group) {
+                return Err(Error::InvalidSignature);
+            }
+            PublicKey::Bls(group).verify(
+                &proof.debiting_replicas_sig,
+                &utils::serialise(&proof.signed_credit)?,
+            )
+        };
+        match self {
+            Self::TransferValidationProposed(event) => event.verify(),
+            Self::TransferValidated(event) => {
+                if &event.replicas != replicas {
+                    return Err(Error::InvalidSignature);
+                }
+                verify_share(replicas, &event.replica_debit_sig, &event.signed_debit)?;
+                verify_share(replicas, &event.replica_credit_sig, &event.signed_credit)
+            }
+            Self::TransferRegistered(event) => {
+                let proof = &event.transfer_proof;
+                if &proof.debiting_replicas_keys != replicas {
+                    return Err(Error::InvalidSignature);
+                }
+                let key = PublicKey::Bls(replicas.public_key());
+                key.verify(&proof.debit_sig, &utils::serialise(&proof.signed_debit)?)?;
+                key.verify(&proof.credit_sig, &utils::serialise(&proof.signed_credit)?)
+            }
+            Self::TransferPropagated(event) => verify_credit_proof(&event.credit_proof),
+            Self::TransferRefunded(event) => {
+                event.validate()?;
+                verify_credit_proof(&event.credit_proof)
+            }
+            Self::GenesisCredited(event) => {
+                event.validate()?;
+                verify_credit_proof(&event.credit_proof)
+            }
+            Self::TransferValidationCancelled(event) => event.verify(),
+        }
+    }
+}
+
+fn verify_share<T: Serialize>(
+    replicas: &ReplicaPublicKeySet,
+    share: &SignatureShare,
+    data: &T,
+) -> Result<()> {
+    let key = PublicKey::BlsShare(replicas.public_key_share(share.index));
+    key.verify(
+        &Signature::BlsShare(share.clone()),
+        &utils::serialise(data)?,
+    )
+}
+
+/// Checks the consistency of a Replica event log as it is applied:
+/// the signatures of each event, that the debits of each Actor are
+/// validated and registered in strictly sequential order, that no
+/// transfer or credit appears twice with different contents, that no Actor
+/// debits more native tokens than it holds, and that the native tokens held
+/// never exceed the genesis supply.
+///
+/// Credits are only accepted from the Replicas themselves and from the groups
+/// added with `add_known_group`. A credit seen again unchanged is ignored.
+/// The genesis credit is only accepted from the key pinned with `with_genesis_key`.
+/// Time-locks are checked against the system time, or the clock given with `with_clock`.
+#[derive(Clone)]
+pub struct ReplicaHistoryValidator {
+    replicas: ReplicaPublicKeySet,
+    known_groups: BTreeSet<threshold_crypto::PublicKey>,
+    genesis_key: Option<threshold_crypto::PublicKey>,
+    clock: Arc<dyn Clock + Send + Sync>,
+    next_debit: BTreeMap<PublicKey, u64>,
+    validated: BTreeMap<(PublicKey, u64), SignedDebit>,
+    credits: BTreeMap<CreditId, SignedCredit>,
+    refunded: BTreeSet<(PublicKey, u64)>,
+    genesis: bool,
+    held: Token,
+    balances: BTreeMap<PublicKey, Token>,
+}
+
+impl Debug for ReplicaHistoryValidator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ReplicaHistoryValidator {{ replicas: PkSet {{ public_key: {:?} }}, next_debit: {:?} }}",
+            self.replicas.public_key(),
+            self.next_debit
+        )
+    }
+}
+
+impl ReplicaHistoryValidator {
+    /// Constructs a validator for the event log of the given Replicas.
+    pub fn new(replicas: ReplicaPublicKeySet) -> Self {
+        Self {
+            replicas,
+            known_groups: Default::default(),
+            genesis_key: None,
+            clock: Arc::new(SystemClock),
+            next_debit: Default::default(),
+            validated: Default::default(),
+            credits: Default::default(),
+            refunded: Default::default(),
+            genesis: false,
+            held: Token::zero(),
+            balances: Default::default(),
+        }
+    }
+
+    /// Pins the key of the group which credits the genesis supply. Without it, no genesis
+    /// credit is accepted. The key must also be that of the Replicas or of a known group.
+    pub fn with_genesis_key(mut self, key: threshold_crypto::PublicKey) -> Self {
+        self.genesis_key = Some(key);
+        self
+    }
+
+    /// Checks the time-lock of each registered transfer against `clock` instead of the
+    /// system time, e.g. a `SimulatedClock` to replay a history deterministically.
+    pub fn with_clock(mut self, clock: impl Clock + Send + Sync + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Trusts the credits signed by the group from now on.
+    pub fn add_known_group(&mut self, event: &KnownGroupAdded) {
+        let _ = self.known_groups.insert(event.group.public_key());
+    }
+
+    /// Checks the event against the events validated so far, and records it if valid.
+    /// An invalid event leaves the validator unchanged.
+    ///
+    /// Returns `Error::InvalidSignature` if the event is not signed by a trusted key,
+    /// `Error::InvalidSuccessor` with the expected debit version if a debit
+    /// is out of order, `Error::ConflictingReplicaEvent` on a fork,
+    /// `Error::SupplyExceeded` if the native tokens held would exceed the genesis supply,
+    /// `Error::InvalidOperation` if a debit would take more native tokens than its sender holds
+    /// or a cancelled debit was not validated,
+    /// and `Error::TransferTimeLocked` if a transfer is registered before its time-lock.
+    pub fn validate(&mut self, event: &ReplicaEvent) -> Result<()> {
+        if let ReplicaEvent::GenesisCredited(event) = event {
+            let key = event.credit_proof.debiting_replicas_keys.public_key();
+            if self.genesis_key != Some(key) {
+                return Err(Error::InvalidSignature);
+            }
+        }
+        event.verify(&self.replicas, &self.known_groups)?;
+        if let ReplicaEvent::TransferRegistered(event) = event {
+            event.transfer_proof.check_not_before(self.clock.now())?;
+        }
+        if self.is_recorded_credit(event)? {
+            return Ok(());
+        }
+        let (held, balance) = self.supply_after(event)?;
+        self.record(event)?;
+        self.held = held;
+        if let Some((key, balance)) = balance {
+            let _ = self.balances.insert(key, balance);
+        }
+        Ok(())
+    }
+
+    // Records the event, checking it against the events recorded so far.
+    fn record(&mut self, event: &ReplicaEvent) -> Result<()> {
+        match event {
+            ReplicaEvent::TransferValidationProposed(event) => self.check_next_debit(event.id()),
+            ReplicaEvent::TransferValidated(event) => {
+                let id = event.id();
+                self.check_next_debit(id)?;
+                match self.validated.get(&(id.actor, id.counter)) {
+                    Some(debit) if debit != &event.signed_debit => {
+                        Err(Error::ConflictingReplicaEvent)
+                    }
+                    Some(_) => Ok(()),
+                    None => {
+                        let _ = self
+                            .validated
+                            .insert((id.actor, id.counter), event.signed_debit.clone());
+                        Ok(())
+                    }
+                }
+            }
+            ReplicaEvent::TransferRegistered(event) => {
+                let id = event.id();
+                self.check_next_debit(id)?;
+                if let Some(debit) = self.validated.get(&(id.actor, id.counter)) {
+                    if debit != &event.transfer_proof.signed_debit {
+                        return Err(Error::ConflictingReplicaEvent);
+                    }
+                }
+                let _ = self.validated.remove(&(id.actor, id.counter));
+                let _ = self.next_debit.insert(id.actor, id.counter + 1);
+                Ok(())
+            }
+            ReplicaEvent::TransferPropagated(event) => {
+                self.record_credit(&event.credit_proof);
+                Ok(())
+            }
+            ReplicaEvent::TransferRefunded(event) => {
+                let debit = event.original_debit;
+                if !self.refunded.insert((debit.actor, debit.counter)) {
+                    return Err(Error::ConflictingReplicaEvent);
+                }
+                self.record_credit(&event.credit_proof);
+                Ok(())
+            }
+            ReplicaEvent::GenesisCredited(event) => {
+                if self.genesis {
+                    return Err(Error::ConflictingReplicaEvent);
+                }
+                self.genesis = true;
+                self.record_credit(&event.credit_proof);
+                Ok(())
+            }
+            ReplicaEvent::TransferValidationCancelled(event) => {
+                let id = event.id();
+                self.check_next_debit(id)?;
+                match self.validated.get(&(id.actor, id.counter)) {
+                    Some(debit) if debit != &event.signed_debit => {
+                        Err(Error::ConflictingReplicaEvent)
+                    }
+                    Some(_) => {
+                        let _ = self.validated.remove(&(id.actor, id.counter));
+                        Ok(())
+                    }
+                    // Only a validated debit can be cancelled.
+                    None => Err(Error::InvalidOperation),
+                }
+            }
+        }
+    }
+
+    // Returns the native tokens held by the Replicas' keys after the event, which
+    // credits increase and registered debits decrease, along with the new balance of
+    // the key it changes, checking they stay within the genesis supply and that no
+    // debit takes more than its sender holds.
+    fn supply_after(&self, event: &ReplicaEvent) -> Result<(Token, Option<(PublicKey, Token)>)> {
+        let credit = |recipient, amount| {
+            let held = self
+                .held
+                .checked_add(amount)
+                .filter(|held| *held <= GENESIS_SUPPLY)
+                .ok_or(Error::SupplyExceeded)?;
+            // Balances add up to what is held, so the checks above cover them too.
+            let balance = self
+                .balance(&recipient)
+                .checked_add(amount)
+                .ok_or(Error::SupplyExceeded)?;
+            Ok((held, Some((recipient, balance))))
+        };
+        match event {
+            ReplicaEvent::TransferRegistered(event) if event.token().is_native() => {
+                let sender = event.sender();
+                let balance = self
+                    .balance(&sender)
+                    .checked_sub(event.amount())
+                    .ok_or(Error::InvalidOperation)?;
+                let held = self
+                    .held
+                    .checked_sub(event.amount())
+                    .ok_or(Error::InvalidOperation)?;
+                Ok((held, Some((sender, balance))))
+            }
+            ReplicaEvent::TransferPropagated(event) if event.token().is_native() => {
+                credit(event.recipient(), event.amount())
+            }
+            ReplicaEvent::TransferRefunded(event) if event.token().is_native() => {
+                credit(event.recipient(), event.amount())
+            }
+            ReplicaEvent::GenesisCredited(event) => credit(event.recipient(), event.amount()),
+            _ => Ok((self.held, None)),
+        }
+    }
+
+    // Returns the native tokens held by the key.
+    fn balance(&self, key: &PublicKey) -> Token {
+        self.balances.get(key).copied().unwrap_or_else(Token::zero)
+    }
+
+    // Returns `true` if the event is a credit already recorded unchanged, and
+    // `Error::ConflictingReplicaEvent` if one with the same id differs from it.
+    fn is_recorded_credit(&self, event: &ReplicaEvent) -> Result<bool> {
+        let credit = match event {
+            ReplicaEvent::TransferPropagated(event) => &event.credit_proof.signed_credit,
+            ReplicaEvent::TransferRefunded(event) => &event.credit_proof.signed_credit,
+            ReplicaEvent::GenesisCredited(event) => &event.credit_proof.signed_credit,
+            _ => return Ok(false),
+        };
+        match self.credits.get(credit.id()) {
+            Some(recorded) if recorded == credit => Ok(true),
+            Some(_) => Err(Error::ConflictingReplicaEvent),
+            None => Ok(false),
+        }
+    }
+
+    fn record_credit(&mut self, proof: &CreditAgreementProof) {
+        let _ = self
+            .credits
+            .insert(*proof.id(), proof.signed_credit.clone());
+    }
+
+    fn check_next_debit(&self, id: DebitId) -> Result<()> {
+        let expected = self.next_debit.get(&id.actor).copied().unwrap_or(0);
+        if id.counter == expected {
+            Ok(())
+        } else {
+            Err(Error::InvalidSuccessor(expected))
+        }
+    }
+}
+
+/// The Replica events of the history of a key, as returned to its wallet.
+///
+/// The registered debits of the key are checked on construction to be in sequence,
+/// so the last one is the latest debit of the key. Derefs to the slice of events.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct TransferHistory {
+    owner: PublicKey,
+    events: Vec<ReplicaEvent>,
+}
+
+impl TransferHistory {
+    /// Creates the history of `owner` from its Replica events.
+    ///
+    /// Returns `Error::InvalidSuccessor` with the expected debit version if the
+    /// registered debits of `owner` are out of order.
+    pub fn new(owner: PublicKey, events: Vec<ReplicaEvent>) -> Result<Self> {
+        let mut expected = 0;
+        for event in &events {
+            if let ReplicaEvent::TransferRegistered(event) = event {
+                let id = event.id();
+                if id.actor != owner {
+                    continue;
+                }
+                if id.counter != expected {
+                    return Err(Error::InvalidSuccessor(expected));
+                }
+                expected += 1;
+            }
+        }
+        Ok(Self { owner, events })
+    }
+
+    /// Returns the key whose history this is.
+    pub fn owner(&self) -> &PublicKey {
+        &self.owner
+    }
+
+    /// Returns the balance of the owner in `token` after all the events.
+    pub fn balance(&self, token: TokenId) -> Result<Token> {
+        balance_at(&self.events, &self.owner, token, self.events.len())
+    }
+
+    /// Returns the latest registered debit of the owner.
+    pub fn last_debit(&self) -> Option<&TransferAgreementProof> {
+        self.events.iter().rev().find_map(|event| match event {
+            ReplicaEvent::TransferRegistered(event) if event.sender() == self.owner => {
+                Some(&event.transfer_proof)
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns the credits to the owner from the events after the first `version` ones.
+    pub fn credits_since(&self, version: usize) -> impl Iterator<Item = &CreditAgreementProof> {
+        let owner = self.owner;
+        self.events
+            .iter()
+            .skip(version)
+            .filter_map(move |event| match event {
+                ReplicaEvent::TransferPropagated(event) if event.recipient() == owner => {
+                    Some(&event.credit_proof)
+                }
+                ReplicaEvent::TransferRefunded(event) if event.recipient() == owner => {
+                    Some(&event.credit_proof)
+                }
+                ReplicaEvent::GenesisCredited(event) if event.recipient() == owner => {
+                    Some(&event.credit_proof)
+                }
+                _ => None,
+            })
+    }
+
+    /// Returns the events.
+    pub fn into_events(self) -> Vec<ReplicaEvent> {
+        self.events
+    }
+}
+
+impl Deref for TransferHistory {
+    type Target = [ReplicaEvent];
+
+    fn deref(&self) -> &Self::Target {
+        &self.events
+    }
 }
 
 /// The debiting Replica event raised when
@@ -607,6 +1247,11 @@ impl TransferValidationProposed {
         self.signed_debit.amount()
     }
 
+    /// Get the token of this transfer
+    pub fn token(&self) -> TokenId {
+        self.signed_debit.token()
+    }
+
     /// Get the sender of this transfer
     pub fn sender(&self) -> PublicKey {
         self.signed_debit.sender()
@@ -616,6 +1261,32 @@ impl TransferValidationProposed {
     pub fn recipient(&self) -> PublicKey {
         self.signed_credit.recipient()
     }
+
+    /// Returns `Ok(())` if the debit and credit shares match, and the agreed transfer,
+    /// if any, is of the same debit and credit and signed by the sender.
+    ///
+    /// The signature shares can only be checked against the Actors' key set,
+    /// which the Replicas accumulating them hold.
+    pub fn verify(&self) -> Result<()> {
+        let (debit, credit) = (&self.signed_debit, &self.signed_credit);
+        if debit.amount() != credit.amount()
+            || debit.token() != credit.token()
+            || debit.credit_id()? != *credit.id()
+            || debit.share_index() != credit.share_index()
+        {
+            return Err(Error::InvalidOperation);
+        }
+        match &self.agreed_transfer {
+            Some(transfer)
+                if transfer.debit.debit != debit.debit
+                    || transfer.credit.credit != credit.credit =>
+            {
+                Err(Error::InvalidOperation)
+            }
+            Some(transfer) => transfer.verify(),
+            None => Ok(()),
+        }
+    }
 }
 
 /// The debiting Replica event raised when
@@ -659,6 +1330,11 @@ impl TransferValidated {
         self.signed_debit.amount()
     }
 
+    /// Get the token of this transfer
+    pub fn token(&self) -> TokenId {
+        self.signed_debit.token()
+    }
+
     /// Get the sender of this transfer
     pub fn sender(&self) -> PublicKey {
         self.signed_debit.sender()
@@ -668,6 +1344,48 @@ impl TransferValidated {
     pub fn recipient(&self) -> PublicKey {
         self.signed_credit.recipient()
     }
+
+    /// Get the quorum rules of the Replicas
+    pub fn replicas_quorum(&self) -> QuorumConfig {
+        QuorumConfig::from(&self.replicas)
+    }
+}
+
+/// The debiting Replica event raised when
+/// CancelValidation cmd has been successful, releasing a debit
+/// which was validated but never registered.
+///
+/// The cancellation names the signed debit itself rather than its id, so that it
+/// cannot be replayed to cancel a later debit validated with the same id.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct TransferValidationCancelled {
+    /// The cancelled debit.
+    pub signed_debit: SignedDebit,
+    /// Actor signature over the signed debit.
+    pub actor_signature: Signature,
+}
+
+impl TransferValidationCancelled {
+    /// Signs the cancellation of the debit with the Actor's keypair.
+    pub fn new(signed_debit: SignedDebit, keypair: &Keypair) -> Result<Self> {
+        Ok(Self {
+            actor_signature: keypair.sign(&utils::serialise(&signed_debit)?),
+            signed_debit,
+        })
+    }
+
+    /// Get the debit id
+    pub fn id(&self) -> DebitId {
+        self.signed_debit.id()
+    }
+
+    /// Returns `Ok(())` if the cancellation is signed by the Actor of the debit.
+    pub fn verify(&self) -> Result<()> {
+        self.signed_debit.sender().verify(
+            &self.actor_signature,
+            &utils::serialise(&self.signed_debit)?,
+        )
+    }
 }
 
 /// The debiting Replica event raised when
@@ -689,6 +1407,11 @@ impl TransferRegistered {
         self.transfer_proof.amount()
     }
 
+    /// Get the token of this transfer
+    pub fn token(&self) -> TokenId {
+        self.transfer_proof.token()
+    }
+
     /// Get the sender of this transfer
     pub fn sender(&self) -> PublicKey {
         self.transfer_proof.sender()
@@ -719,14 +1442,104 @@ impl TransferPropagated {
         self.credit_proof.amount()
     }
 
+    /// Get the token of this transfer
+    pub fn token(&self) -> TokenId {
+        self.credit_proof.token()
+    }
+
     /// Get the recipient of this credit
     pub fn recipient(&self) -> PublicKey {
         self.credit_proof.recipient()
     }
 }
 
+/// The crediting Replica event raised when
+/// a store payment has been refunded to its sender, after the paid write failed.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct TransferRefunded {
+    /// The id of the debit which paid for the failed write.
+    pub original_debit: DebitId,
+    /// The proof of the credit returning the payment to the sender.
+    pub credit_proof: CreditAgreementProof,
+}
+
+impl TransferRefunded {
+    /// Get the credit id
+    pub fn id(&self) -> &CreditId {
+        self.credit_proof.id()
+    }
+
+    /// Get the amount of this refund
+    pub fn amount(&self) -> Token {
+        self.credit_proof.amount()
+    }
+
+    /// Get the token of this refund
+    pub fn token(&self) -> TokenId {
+        self.credit_proof.token()
+    }
+
+    /// Get the recipient of this refund
+    pub fn recipient(&self) -> PublicKey {
+        self.credit_proof.recipient()
+    }
+
+    /// Returns `Err(InvalidOperation)` unless the refund is credited
+    /// back to the sender of the original debit.
+    pub fn validate(&self) -> Result<()> {
+        if self.recipient() == self.original_debit.actor {
+            Ok(())
+        } else {
+            Err(Error::InvalidOperation)
+        }
+    }
+}
+
+/// The proof of the genesis credit, which brings the native token
+/// supply into existence on the first section.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GenesisProof {
+    /// The proof of the credit of the genesis supply.
+    pub credit_proof: CreditAgreementProof,
+}
+
+impl GenesisProof {
+    /// Get the credit id
+    pub fn id(&self) -> &CreditId {
+        self.credit_proof.id()
+    }
+
+    /// Get the amount of the genesis credit
+    pub fn amount(&self) -> Token {
+        self.credit_proof.amount()
+    }
+
+    /// Get the token of the genesis credit
+    pub fn token(&self) -> TokenId {
+        self.credit_proof.token()
+    }
+
+    /// Get the recipient of the genesis credit
+    pub fn recipient(&self) -> PublicKey {
+        self.credit_proof.recipient()
+    }
+
+    /// Returns `Err(InvalidOperation)` unless the credit is of the native token,
+    /// and `Err(SupplyExceeded)` if it is larger than the genesis supply.
+    pub fn validate(&self) -> Result<()> {
+        if !self.token().is_native() {
+            Err(Error::InvalidOperation)
+        } else if self.amount() > GENESIS_SUPPLY {
+            Err(Error::SupplyExceeded)
+        } else {
+            Ok(())
+        }
+    }
+}
+
 /// Public Key Set for a group of transfer replicas.
 pub type ReplicaPublicKeySet = PublicKeySet;
+
 /// The Replica event raised when
 /// we learn of a new group PK set.
 #[derive(Clone, Hash, Eq, PartialEq, PartialOrd, Serialize, Deserialize)]
@@ -748,3 +1561,1035 @@ impl Debug for KnownGroupAdded {
 /// Notification of a credit sent to a recipient.
 #[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
 pub struct CreditNotification(pub CreditAgreementProof);
+
+/// Notification of a new balance, sent to the owner of a key which received a credit.
+#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct BalanceUpdated {
+    /// The key whose balance changed.
+    pub owner: PublicKey,
+    /// The balance after applying the credit.
+    pub new_balance: Token,
+    /// The credit which caused the change.
+    pub cause: CreditId,
+}
+
+impl BalanceUpdated {
+    /// Returns the notification for the latest credit to the owner of `history`,
+    /// with the balance right after that credit, or `None` if it has received none.
+    pub fn latest(history: &TransferHistory) -> Result<Option<Self>> {
+        let owner = *history.owner();
+        let latest = history
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(index, event)| match event {
+                ReplicaEvent::TransferPropagated(event) if event.recipient() == owner => {
+                    Some((index, &event.credit_proof))
+                }
+                ReplicaEvent::TransferRefunded(event) if event.recipient() == owner => {
+                    Some((index, &event.credit_proof))
+                }
+                ReplicaEvent::GenesisCredited(event) if event.recipient() == owner => {
+                    Some((index, &event.credit_proof))
+                }
+                _ => None,
+            });
+        let (index, credit) = match latest {
+            Some(latest) => latest,
+            None => return Ok(None),
+        };
+        Ok(Some(Self {
+            owner,
+            new_balance: balance_at(history, &owner, credit.token(), index + 1)?,
+            cause: *credit.id(),
+        }))
+    }
+}
+
+/// A balance as reported by a single Replica, signed with its key share,
+/// so that a client can compare the answers of several Replicas and detect
+/// one reporting a false balance.
+#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct SignedBalance {
+    /// The key whose balance this is.
+    pub owner: PublicKey,
+    /// The balance.
+    pub amount: Token,
+    /// Number of events in the key's history when the balance was read.
+    pub at_version: u64,
+    /// The Replica's signature share over the serialised
+    /// `(owner, amount, at_version, token)`.
+    pub replica_sig: SignatureShare,
+    /// The token of the balance.
+    #[serde(default)]
+    pub token: TokenId,
+}
+
+impl SignedBalance {
+    /// Verifies the signature share against the given Replicas.
+    pub fn verify(&self, replicas: &ReplicaPublicKeySet) -> Result<()> {
+        verify_share(
+            replicas,
+            &self.replica_sig,
+            &(&self.owner, &self.amount, self.at_version, &self.token),
+        )
+    }
+}
+
+/// The history of a key as reported by a single Replica, signed with its key share.
+#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct SignedHistory {
+    /// The key whose history this is.
+    pub owner: PublicKey,
+    /// The history.
+    pub history: ActorHistory,
+    /// Number of events in the key's history when it was read.
+    pub at_version: u64,
+    /// The Replica's signature share over the serialised
+    /// `(owner, history, at_version, token)`.
+    pub replica_sig: SignatureShare,
+    /// The token the history was read for.
+    #[serde(default)]
+    pub token: TokenId,
+}
+
+impl SignedHistory {
+    /// Verifies the signature share against the given Replicas.
+    pub fn verify(&self, replicas: &ReplicaPublicKeySet) -> Result<()> {
+        verify_share(
+            replicas,
+            &self.replica_sig,
+            &(&self.owner, &self.history, self.at_version, &self.token),
+        )
+    }
+}
+
+/// When the payments of a `PaymentIntent` fall due.
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Debug)]
+pub struct PaymentSchedule {
+    /// Time of the first payment, in seconds since the UNIX epoch.
+    pub start: u64,
+    /// Seconds between consecutive payments.
+    pub interval: u64,
+}
+
+impl PaymentSchedule {
+    /// Returns the time at which the payment with the given zero-based index falls due,
+    /// or `None` on overflow.
+    pub fn due_at(&self, execution: u64) -> Option<u64> {
+        self.interval
+            .checked_mul(execution)
+            .and_then(|offset| self.start.checked_add(offset))
+    }
+}
+
+/// A standing order for recurring payments, signed by the paying key.
+///
+/// Only the agreed format lives here: the payments themselves are made as
+/// ordinary transfers by whichever party executes the order.
+#[derive(Clone, Hash, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Debug)]
+pub struct PaymentIntent {
+    /// The key paying, which must sign the intent.
+    pub payer: PublicKey,
+    /// Chosen by the payer to tell apart otherwise identical intents.
+    pub nonce: u64,
+    /// The amount of each payment.
+    pub amount: Token,
+    /// The token of each payment.
+    pub token: TokenId,
+    /// The key to pay.
+    pub recipient: PublicKey,
+    /// When the payments fall due.
+    pub schedule: PaymentSchedule,
+    /// The maximum number of payments to make.
+    pub max_executions: u64,
+    /// Msg, passed on to the recipient with each payment.
+    pub msg: Msg,
+}
+
+/// A `PaymentIntent` signed by the paying key.
+pub type SignedPaymentIntent = SignedWrite<PaymentIntent>;
+
+impl PaymentIntent {
+    /// Returns the canonical serialisation of the intent, from which its id is derived.
+    /// The signature of a `SignedPaymentIntent` covers the intent together with the op id
+    /// of the write, see `SignedWrite`.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        utils::serialise(self)
+    }
+
+    /// Returns the unique id of the intent, the hash of its canonical serialisation.
+    pub fn id(&self) -> Result<[u8; 32]> {
+        let mut hasher = Sha3::v256();
+        let mut output = [0; 32];
+        hasher.update(&self.to_bytes()?);
+        hasher.finalize(&mut output);
+        Ok(output)
+    }
+
+    /// Signs the intent with the keypair of the paying key.
+    ///
+    /// Returns `Err(InvalidSignature)` if the keypair is not that of the payer.
+    pub fn sign(self, keypair: &Keypair) -> Result<SignedPaymentIntent> {
+        if keypair.public_key() != self.payer {
+            return Err(Error::InvalidSignature);
+        }
+        SignedWrite::new(self, keypair)
+    }
+
+    /// Returns `Ok(())` if the intent was signed by its payer, and
+    /// `Err(Error::InvalidSignature)` otherwise.
+    pub fn verify(signed: &SignedPaymentIntent) -> Result<()> {
+        if signed.requester != signed.op.payer {
+            return Err(Error::InvalidSignature);
+        }
+        signed.verify()
+    }
+
+    /// Returns `true` if another payment is due at `now`, given the number already made.
+    pub fn is_due(&self, executions: u64, now: u64) -> bool {
+        executions < self.max_executions
+            && matches!(self.schedule.due_at(executions), Some(due) if due <= now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        balance_at, ActorHistory, BalanceUpdated, Credit, CreditAgreementProof, Error,
+        GenesisProof, Keypair, KnownGroupAdded, PaymentIntent, PaymentSchedule, PublicKey,
+        ReplicaEvent, ReplicaHistoryValidator, Result, Signature, SignatureShare, SignedBalance,
+        SignedCreditShare, SignedDebitShare, SignedHistory, SignedTransferShare, SignedWrite,
+        Token, TokenId, Transfer, TransferAgreementProof, TransferHistory, TransferPropagated,
+        TransferRefunded, TransferRegistered, TransferValidated, TransferValidationCancelled,
+        TransferValidationProposed, GENESIS_SUPPLY,
+    };
+    use crate::{utils, SimulatedClock};
+    use crdts::Dot;
+    use rand::rngs::OsRng;
+    use threshold_crypto::{SecretKey, SecretKeySet};
+    use xor_name::XorName;
+
+    fn transfer(sender: PublicKey) -> Transfer {
+        transfer_with(sender, Keypair::new_ed25519(&mut OsRng).public_key(), 0)
+    }
+
+    fn transfer_with(sender: PublicKey, to: PublicKey, counter: u64) -> Transfer {
+        Transfer {
+            amount: Token::from_nano(10),
+            token: TokenId::NATIVE,
+            to,
+            debit_id: Dot::new(sender, counter),
+            msg: "msg".to_string(),
+            not_before: 0,
+        }
+    }
+
+    // Events signed by random Replicas, which `TransferHistory` does not check.
+    fn registered(transfer: &Transfer, sender: &SecretKey) -> Result<ReplicaEvent> {
+        registered_by(transfer, sender, &SecretKeySet::random(0, &mut OsRng))
+    }
+
+    fn propagated(transfer: &Transfer, sender: &SecretKey) -> Result<ReplicaEvent> {
+        propagated_by(transfer, sender, &SecretKeySet::random(0, &mut OsRng))
+    }
+
+    fn registered_by(
+        transfer: &Transfer,
+        sender: &SecretKey,
+        replicas: &SecretKeySet,
+    ) -> Result<ReplicaEvent> {
+        let signed = transfer.sign_bls(sender)?;
+        // The share of a threshold-0 set signs on its own for the whole set.
+        let secret_key = replicas.secret_key_share(0);
+        let sign = |bytes: Vec<u8>| Signature::Bls(secret_key.sign(&bytes).0);
+        Ok(ReplicaEvent::TransferRegistered(TransferRegistered {
+            transfer_proof: TransferAgreementProof {
+                debit_sig: sign(utils::serialise(&signed.debit)?),
+                credit_sig: sign(utils::serialise(&signed.credit)?),
+                signed_debit: signed.debit,
+                signed_credit: signed.credit,
+                debiting_replicas_keys: replicas.public_keys(),
+            },
+        }))
+    }
+
+    fn propagated_by(
+        transfer: &Transfer,
+        sender: &SecretKey,
+        replicas: &SecretKeySet,
+    ) -> Result<ReplicaEvent> {
+        match registered_by(transfer, sender, replicas)? {
+            ReplicaEvent::TransferRegistered(event) => {
+                let proof = event.transfer_proof;
+                Ok(ReplicaEvent::TransferPropagated(TransferPropagated {
+                    credit_proof: CreditAgreementProof {
+                        signed_credit: proof.signed_credit,
+                        debiting_replicas_sig: proof.credit_sig,
+                        debiting_replicas_keys: proof.debiting_replicas_keys,
+                    },
+                }))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn transfer_history() -> Result<()> {
+        let owner_secret = SecretKey::random();
+        let owner = PublicKey::Bls(owner_secret.public_key());
+        let other_secret = SecretKey::random();
+        let other = PublicKey::Bls(other_secret.public_key());
+
+        let events = vec![
+            propagated(&transfer_with(other, owner, 0), &other_secret)?,
+            registered(&transfer_with(owner, other, 0), &owner_secret)?,
+            propagated(&transfer_with(other, owner, 1), &other_secret)?,
+            registered(&transfer_with(owner, other, 1), &owner_secret)?,
+        ];
+        let history = TransferHistory::new(owner, events.clone())?;
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.balance(TokenId::NATIVE)?, Token::zero());
+        assert_eq!(
+            history.last_debit().map(|proof| proof.id().counter),
+            Some(1)
+        );
+        assert_eq!(history.credits_since(0).count(), 2);
+        assert_eq!(history.credits_since(1).count(), 1);
+        assert_eq!(history.credits_since(3).count(), 0);
+
+        let mut reordered = events;
+        reordered.swap(1, 3);
+        assert_eq!(
+            TransferHistory::new(owner, reordered),
+            Err(Error::InvalidSuccessor(0))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn balance_at_version() -> Result<()> {
+        let owner_secret = SecretKey::random();
+        let owner = PublicKey::Bls(owner_secret.public_key());
+        let other_secret = SecretKey::random();
+        let other = PublicKey::Bls(other_secret.public_key());
+
+        let events = vec![
+            propagated(&transfer_with(other, owner, 0), &other_secret)?,
+            propagated(&transfer_with(other, owner, 1), &other_secret)?,
+            registered(&transfer_with(owner, other, 0), &owner_secret)?,
+        ];
+        let balance = |key, version| balance_at(&events, key, TokenId::NATIVE, version);
+        assert_eq!(balance(&owner, 0)?, Token::zero());
+        assert_eq!(balance(&owner, 1)?, Token::from_nano(10));
+        assert_eq!(balance(&owner, 2)?, Token::from_nano(20));
+        assert_eq!(balance(&owner, 3)?, Token::from_nano(10));
+        assert_eq!(balance(&owner, 4), Err(Error::InvalidOperation));
+
+        assert_eq!(balance(&other, 2)?, Token::zero());
+        // A debit without the credits funding it overdraws the key.
+        let overdrawn = vec![registered(&transfer_with(owner, other, 0), &owner_secret)?];
+        assert_eq!(
+            balance_at(&overdrawn, &owner, TokenId::NATIVE, 1),
+            Err(Error::InvalidOperation)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn balance_per_token() -> Result<()> {
+        let owner_secret = SecretKey::random();
+        let owner = PublicKey::Bls(owner_secret.public_key());
+        let other_secret = SecretKey::random();
+        let other = PublicKey::Bls(other_secret.public_key());
+        let token = TokenId(XorName([1; 32]));
+        let in_token = |mut transfer: Transfer| {
+            transfer.token = token;
+            transfer
+        };
+
+        let events = vec![
+            propagated(&transfer_with(other, owner, 0), &other_secret)?,
+            propagated(&in_token(transfer_with(other, owner, 1)), &other_secret)?,
+            propagated(&in_token(transfer_with(other, owner, 2)), &other_secret)?,
+            registered(&in_token(transfer_with(owner, other, 0)), &owner_secret)?,
+        ];
+        let history = TransferHistory::new(owner, events.clone())?;
+        assert_eq!(history.balance(TokenId::NATIVE)?, Token::from_nano(10));
+        assert_eq!(history.balance(token)?, Token::from_nano(10));
+        assert_eq!(history.balance(TokenId(XorName([2; 32])))?, Token::zero());
+        assert_eq!(balance_at(&events, &owner, token, 3)?, Token::from_nano(20));
+        assert_eq!(
+            balance_at(&events, &owner, TokenId::NATIVE, 3)?,
+            Token::from_nano(10)
+        );
+
+        // A debit in one token is not funded by credits in another.
+        let overdrawn = vec![
+            propagated(&transfer_with(other, owner, 0), &other_secret)?,
+            registered(&in_token(transfer_with(owner, other, 0)), &owner_secret)?,
+        ];
+        assert_eq!(
+            balance_at(&overdrawn, &owner, token, 2),
+            Err(Error::InvalidOperation)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn balance_updated() -> Result<()> {
+        let owner_secret = SecretKey::random();
+        let owner = PublicKey::Bls(owner_secret.public_key());
+        let other_secret = SecretKey::random();
+        let other = PublicKey::Bls(other_secret.public_key());
+
+        let history = TransferHistory::new(
+            owner,
+            vec![registered(&transfer_with(other, owner, 0), &other_secret)?],
+        )?;
+        assert_eq!(BalanceUpdated::latest(&history)?, None);
+
+        let first = transfer_with(other, owner, 0);
+        let second = transfer_with(other, owner, 1);
+        let history = TransferHistory::new(
+            owner,
+            vec![
+                propagated(&first, &other_secret)?,
+                propagated(&second, &other_secret)?,
+                registered(&transfer_with(owner, other, 0), &owner_secret)?,
+            ],
+        )?;
+        assert_eq!(
+            BalanceUpdated::latest(&history)?,
+            Some(BalanceUpdated {
+                owner,
+                new_balance: Token::from_nano(20),
+                cause: second.credit()?.id,
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn transfer_signature_schemes() -> Result<()> {
+        let ed25519 = Keypair::new_ed25519(&mut OsRng);
+        let bls = SecretKey::random();
+        let ed25519_key = ed25519.public_key();
+        let bls_key = PublicKey::Bls(bls.public_key());
+
+        transfer(ed25519_key).sign(&ed25519)?.verify()?;
+        transfer(bls_key).sign_bls(&bls)?.verify()?;
+
+        assert_eq!(
+            transfer(ed25519_key).sign_bls(&bls).map(|_| ()),
+            Err(Error::SigningKeyTypeMismatch)
+        );
+        assert_eq!(
+            transfer(bls_key).sign(&ed25519).map(|_| ()),
+            Err(Error::SigningKeyTypeMismatch)
+        );
+        let other = Keypair::new_ed25519(&mut OsRng);
+        assert_eq!(
+            transfer(ed25519_key).sign(&other).map(|_| ()),
+            Err(Error::InvalidSignature)
+        );
+        assert_eq!(
+            transfer(bls_key).sign_bls(&SecretKey::random()).map(|_| ()),
+            Err(Error::InvalidSignature)
+        );
+
+        let mut tampered = transfer(bls_key).sign_bls(&bls)?;
+        tampered.debit.debit.amount = Token::from_nano(1);
+        tampered.credit.credit.amount = Token::from_nano(1);
+        assert_eq!(tampered.verify(), Err(Error::InvalidSignature));
+        Ok(())
+    }
+
+    #[test]
+    fn transfer_share_parts() -> Result<()> {
+        let actors = SecretKeySet::random(0, &mut OsRng);
+        let share = |data: &[u8]| SignatureShare {
+            index: 0,
+            share: actors.secret_key_share(0).sign(data),
+        };
+        let sender = PublicKey::Bls(actors.public_keys().public_key());
+        let transfer = transfer_with(sender, PublicKey::Bls(SecretKey::random().public_key()), 0);
+        let debit = transfer.debit();
+        let signed_debit = SignedDebitShare {
+            actor_signature: share(&utils::serialise(&debit)?),
+            debit,
+        };
+        let signed_credit = |credit: Credit| -> Result<SignedCreditShare> {
+            Ok(SignedCreditShare {
+                actor_signature: share(&utils::serialise(&credit)?),
+                credit,
+            })
+        };
+
+        let credit = transfer.credit()?;
+        let _ = SignedTransferShare::new(
+            signed_debit.clone(),
+            signed_credit(credit.clone())?,
+            actors.public_keys(),
+        )?;
+
+        // The credit must be of the token debited.
+        let other_token = Credit {
+            token: TokenId(XorName([1; 32])),
+            ..credit
+        };
+        assert_eq!(
+            SignedTransferShare::new(
+                signed_debit,
+                signed_credit(other_token)?,
+                actors.public_keys()
+            )
+            .map(|_| ()),
+            Err(Error::InvalidOperation)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn replica_history_order() -> Result<()> {
+        let replicas = SecretKeySet::random(0, &mut OsRng);
+        let mut validator = ReplicaHistoryValidator::new(replicas.public_keys());
+        let actor_secret = SecretKey::random();
+        let actor = PublicKey::Bls(actor_secret.public_key());
+        let funder_secret = SecretKey::random();
+        let funder = PublicKey::Bls(funder_secret.public_key());
+
+        for counter in 0..3 {
+            let funding = transfer_with(funder, actor, counter);
+            validator.validate(&propagated_by(&funding, &funder_secret, &replicas)?)?;
+        }
+        let first = registered_by(&transfer_with(actor, funder, 0), &actor_secret, &replicas)?;
+        let second = registered_by(&transfer_with(actor, funder, 1), &actor_secret, &replicas)?;
+        assert_eq!(validator.validate(&second), Err(Error::InvalidSuccessor(0)));
+        validator.validate(&first)?;
+        validator.validate(&second)?;
+        assert_eq!(validator.validate(&first), Err(Error::InvalidSuccessor(2)));
+        Ok(())
+    }
+
+    #[test]
+    fn replica_history_supply() -> Result<()> {
+        let genesis_replicas = SecretKeySet::random(0, &mut OsRng);
+        let replicas = SecretKeySet::random(0, &mut OsRng);
+        let mut validator = ReplicaHistoryValidator::new(replicas.public_keys())
+            .with_genesis_key(genesis_replicas.public_keys().public_key());
+        validator.add_known_group(&KnownGroupAdded {
+            group: genesis_replicas.public_keys(),
+        });
+        let actor_secret = SecretKey::random();
+        let actor = PublicKey::Bls(actor_secret.public_key());
+        let genesis_secret = SecretKey::random();
+        let genesis_key = PublicKey::Bls(genesis_secret.public_key());
+        let genesis = |amount, signers: &SecretKeySet| -> Result<ReplicaEvent> {
+            let transfer = Transfer {
+                amount,
+                ..transfer_with(genesis_key, actor, 0)
+            };
+            match propagated_by(&transfer, &genesis_secret, signers)? {
+                ReplicaEvent::TransferPropagated(event) => {
+                    Ok(ReplicaEvent::GenesisCredited(GenesisProof {
+                        credit_proof: event.credit_proof,
+                    }))
+                }
+                _ => unreachable!(),
+            }
+        };
+
+        // A debit of more than is held is refused, and leaves nothing behind.
+        let debit = |amount, counter| {
+            let transfer = Transfer {
+                amount,
+                ..transfer_with(actor, genesis_key, counter)
+            };
+            registered_by(&transfer, &actor_secret, &replicas)
+        };
+        assert_eq!(
+            validator.validate(&debit(Token::from_nano(1), 0)?),
+            Err(Error::InvalidOperation)
+        );
+
+        // Only the pinned key may credit the genesis supply, though the Replicas are trusted.
+        assert_eq!(
+            validator.validate(&genesis(GENESIS_SUPPLY, &replicas)?),
+            Err(Error::InvalidSignature)
+        );
+        let mut unpinned = ReplicaHistoryValidator::new(genesis_replicas.public_keys());
+        assert_eq!(
+            unpinned.validate(&genesis(GENESIS_SUPPLY, &genesis_replicas)?),
+            Err(Error::InvalidSignature)
+        );
+        validator.validate(&genesis(GENESIS_SUPPLY, &genesis_replicas)?)?;
+
+        // Tokens beyond the supply are refused, even when their sum would overflow.
+        let sender_secret = SecretKey::random();
+        let sender = PublicKey::Bls(sender_secret.public_key());
+        for (counter, amount) in [1, u64::MAX].iter().enumerate() {
+            let transfer = Transfer {
+                amount: Token::from_nano(*amount),
+                ..transfer_with(sender, actor, counter as u64)
+            };
+            assert_eq!(
+                validator.validate(&propagated_by(&transfer, &sender_secret, &replicas)?),
+                Err(Error::SupplyExceeded)
+            );
+        }
+
+        // A refused event is not counted: the whole supply can still be debited, once.
+        validator.validate(&debit(GENESIS_SUPPLY, 0)?)?;
+        assert_eq!(
+            validator.validate(&debit(Token::from_nano(1), 1)?),
+            Err(Error::InvalidOperation)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn replica_history_balances() -> Result<()> {
+        let replicas = SecretKeySet::random(0, &mut OsRng);
+        let mut validator = ReplicaHistoryValidator::new(replicas.public_keys());
+        let payer_secret = SecretKey::random();
+        let payer = PublicKey::Bls(payer_secret.public_key());
+        let actors: Vec<_> = (0..3).map(|_| SecretKey::random()).collect();
+        let actor = |index: usize| PublicKey::Bls(actors[index].public_key());
+
+        // The first two Actors are funded, the third is not.
+        for (counter, amount) in [10, 20].iter().enumerate() {
+            let transfer = Transfer {
+                amount: Token::from_nano(*amount),
+                ..transfer_with(payer, actor(counter), counter as u64)
+            };
+            validator.validate(&propagated_by(&transfer, &payer_secret, &replicas)?)?;
+        }
+        let debit = |index: usize, amount, counter| {
+            let transfer = Transfer {
+                amount: Token::from_nano(amount),
+                ..transfer_with(actor(index), payer, counter)
+            };
+            registered_by(&transfer, &actors[index], &replicas)
+        };
+
+        // An Actor may only debit its own balance, whatever the others hold.
+        assert_eq!(
+            validator.validate(&debit(2, 1, 0)?),
+            Err(Error::InvalidOperation)
+        );
+        assert_eq!(
+            validator.validate(&debit(0, 11, 0)?),
+            Err(Error::InvalidOperation)
+        );
+        validator.validate(&debit(0, 10, 0)?)?;
+        assert_eq!(
+            validator.validate(&debit(0, 1, 1)?),
+            Err(Error::InvalidOperation)
+        );
+        validator.validate(&debit(1, 20, 0)?)?;
+        Ok(())
+    }
+
+    #[test]
+    fn replica_history_forks() -> Result<()> {
+        let replicas = SecretKeySet::random(0, &mut OsRng);
+        let mut validator = ReplicaHistoryValidator::new(replicas.public_keys());
+        let sender_secret = SecretKey::random();
+        let sender = PublicKey::Bls(sender_secret.public_key());
+        let recipient = Keypair::new_ed25519(&mut OsRng).public_key();
+
+        let transfer = transfer_with(sender, recipient, 0);
+        let credit = propagated_by(&transfer, &sender_secret, &replicas)?;
+        validator.validate(&credit)?;
+        // The same credit seen again is ignored, while a different one with its id is a fork.
+        validator.validate(&credit)?;
+        let forked = Transfer {
+            amount: Token::from_nano(11),
+            ..transfer
+        };
+        assert_eq!(
+            validator.validate(&propagated_by(&forked, &sender_secret, &replicas)?),
+            Err(Error::ConflictingReplicaEvent)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn replica_history_forged_events() -> Result<()> {
+        let replicas = SecretKeySet::random(0, &mut OsRng);
+        let mut validator = ReplicaHistoryValidator::new(replicas.public_keys());
+        let sender_secret = SecretKey::random();
+        let sender = PublicKey::Bls(sender_secret.public_key());
+        let recipient = Keypair::new_ed25519(&mut OsRng).public_key();
+        let transfer = transfer_with(sender, recipient, 0);
+
+        // Proofs signed by the keys they carry are only accepted from known groups.
+        let group = SecretKeySet::random(0, &mut OsRng);
+        let credit = propagated_by(&transfer, &sender_secret, &group)?;
+        let registered = registered_by(&transfer, &sender_secret, &group)?;
+        assert_eq!(validator.validate(&credit), Err(Error::InvalidSignature));
+        assert_eq!(
+            validator.validate(&registered),
+            Err(Error::InvalidSignature)
+        );
+        validator.add_known_group(&KnownGroupAdded {
+            group: group.public_keys(),
+        });
+        validator.validate(&credit)?;
+        assert_eq!(
+            validator.validate(&registered),
+            Err(Error::InvalidSignature)
+        );
+
+        let forger = SecretKeySet::random(0, &mut OsRng);
+        let genesis = match propagated_by(
+            &transfer_with(sender, recipient, 1),
+            &sender_secret,
+            &forger,
+        )? {
+            ReplicaEvent::TransferPropagated(event) => GenesisProof {
+                credit_proof: event.credit_proof,
+            },
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            validator.validate(&ReplicaEvent::GenesisCredited(genesis)),
+            Err(Error::InvalidSignature)
+        );
+
+        // A proposal must match the transfer agreed from it.
+        let actors = SecretKeySet::random(0, &mut OsRng);
+        let share = |data: &[u8]| SignatureShare {
+            index: 0,
+            share: actors.secret_key_share(0).sign(data),
+        };
+        let debit = transfer.debit();
+        let credit = transfer.credit()?;
+        let proposal = |agreed_transfer| -> Result<ReplicaEvent> {
+            Ok(ReplicaEvent::TransferValidationProposed(
+                TransferValidationProposed {
+                    signed_debit: SignedDebitShare {
+                        actor_signature: share(&utils::serialise(&debit)?),
+                        debit: debit.clone(),
+                    },
+                    signed_credit: SignedCreditShare {
+                        actor_signature: share(&utils::serialise(&credit)?),
+                        credit: credit.clone(),
+                    },
+                    agreed_transfer,
+                },
+            ))
+        };
+        validator.validate(&proposal(None)?)?;
+        validator.validate(&proposal(Some(transfer.sign_bls(&sender_secret)?))?)?;
+        let other = Transfer {
+            amount: Token::from_nano(11),
+            ..transfer.clone()
+        };
+        assert_eq!(
+            validator.validate(&proposal(Some(other.sign_bls(&sender_secret)?))?),
+            Err(Error::InvalidOperation)
+        );
+        let mut forged = transfer.sign_bls(&sender_secret)?;
+        forged.credit.actor_signature = forged.debit.actor_signature.clone();
+        assert_eq!(
+            validator.validate(&proposal(Some(forged))?),
+            Err(Error::InvalidSignature)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn transfer_refunded() -> Result<()> {
+        let replicas = SecretKeySet::random(0, &mut OsRng);
+        let mut validator = ReplicaHistoryValidator::new(replicas.public_keys());
+        let sender = Keypair::new_ed25519(&mut OsRng).public_key();
+        let refunder_secret = SecretKey::random();
+        let refunder = PublicKey::Bls(refunder_secret.public_key());
+        let refund = |original_debit, counter| -> Result<ReplicaEvent> {
+            let transfer = transfer_with(refunder, sender, counter);
+            match propagated_by(&transfer, &refunder_secret, &replicas)? {
+                ReplicaEvent::TransferPropagated(event) => {
+                    Ok(ReplicaEvent::TransferRefunded(TransferRefunded {
+                        original_debit,
+                        credit_proof: event.credit_proof,
+                    }))
+                }
+                _ => unreachable!(),
+            }
+        };
+
+        // The refund must go back to the sender of the debit it refunds.
+        let misdirected = refund(Dot::new(refunder, 0), 0)?;
+        assert_eq!(
+            validator.validate(&misdirected),
+            Err(Error::InvalidOperation)
+        );
+
+        // A debit is refunded at most once, though the same refund may be seen again.
+        let refunded = refund(Dot::new(sender, 0), 0)?;
+        validator.validate(&refunded)?;
+        validator.validate(&refunded)?;
+        assert_eq!(
+            validator.validate(&refund(Dot::new(sender, 0), 1)?),
+            Err(Error::ConflictingReplicaEvent)
+        );
+        validator.validate(&refund(Dot::new(sender, 1), 1)?)?;
+        Ok(())
+    }
+
+    #[test]
+    fn cancel_validation() -> Result<()> {
+        let actor = Keypair::new_ed25519(&mut OsRng);
+        let recipient = Keypair::new_ed25519(&mut OsRng).public_key();
+        let replicas = SecretKeySet::random(0, &mut OsRng);
+        let mut validator = ReplicaHistoryValidator::new(replicas.public_keys());
+        let validated = |transfer: &Transfer| -> Result<ReplicaEvent> {
+            let signed = transfer.sign(&actor)?;
+            let share = |data: &[u8]| SignatureShare {
+                index: 0,
+                share: replicas.secret_key_share(0).sign(data),
+            };
+            Ok(ReplicaEvent::TransferValidated(TransferValidated {
+                replica_debit_sig: share(&utils::serialise(&signed.debit)?),
+                replica_credit_sig: share(&utils::serialise(&signed.credit)?),
+                signed_debit: signed.debit,
+                signed_credit: signed.credit,
+                replicas: replicas.public_keys(),
+            }))
+        };
+        let cancelled = |transfer: &Transfer, keypair| -> Result<ReplicaEvent> {
+            let debit = transfer.sign(&actor)?.debit;
+            Ok(ReplicaEvent::TransferValidationCancelled(
+                TransferValidationCancelled::new(debit, keypair)?,
+            ))
+        };
+
+        let first = transfer_with(actor.public_key(), recipient, 0);
+        validator.validate(&validated(&first)?)?;
+        validator.validate(&cancelled(&first, &actor)?)?;
+
+        // The cancellation of the first debit cannot cancel another with the same id.
+        let second = Transfer {
+            amount: Token::from_nano(20),
+            ..first.clone()
+        };
+        validator.validate(&validated(&second)?)?;
+        assert_eq!(
+            validator.validate(&cancelled(&first, &actor)?),
+            Err(Error::ConflictingReplicaEvent)
+        );
+        validator.validate(&cancelled(&second, &actor)?)?;
+
+        // Neither a cancelled debit nor one never validated can be cancelled.
+        assert_eq!(
+            validator.validate(&cancelled(&second, &actor)?),
+            Err(Error::InvalidOperation)
+        );
+        let unknown = Transfer {
+            amount: Token::from_nano(30),
+            ..first.clone()
+        };
+        assert_eq!(
+            validator.validate(&cancelled(&unknown, &actor)?),
+            Err(Error::InvalidOperation)
+        );
+
+        let other = Keypair::new_ed25519(&mut OsRng);
+        assert_eq!(
+            validator.validate(&cancelled(&second, &other)?),
+            Err(Error::InvalidSignature)
+        );
+
+        let ahead = transfer_with(actor.public_key(), recipient, 1);
+        assert_eq!(
+            validator.validate(&cancelled(&ahead, &actor)?),
+            Err(Error::InvalidSuccessor(0))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn time_locked_transfer() -> Result<()> {
+        let sender_secret = SecretKey::random();
+        let sender = PublicKey::Bls(sender_secret.public_key());
+        let mut transfer = transfer(sender);
+        transfer.not_before = 1_000;
+        let debit = transfer.debit();
+        assert_eq!(debit.not_before(), 1_000);
+        assert_eq!(
+            debit.check_not_before(999),
+            Err(Error::TransferTimeLocked(1_000))
+        );
+        debit.check_not_before(1_000)?;
+        debit.check_not_before(1_001)?;
+
+        let proof = match registered(&transfer, &sender_secret)? {
+            ReplicaEvent::TransferRegistered(event) => event.transfer_proof,
+            _ => unreachable!(),
+        };
+        assert_eq!(proof.not_before(), 1_000);
+        assert_eq!(
+            proof.check_not_before(0),
+            Err(Error::TransferTimeLocked(1_000))
+        );
+        proof.check_not_before(1_000)?;
+        proof.check_not_before(u64::MAX)?;
+
+        // Without a time-lock the transfer may be registered at any time.
+        transfer.not_before = 0;
+        transfer.debit().check_not_before(0)?;
+        Ok(())
+    }
+
+    #[test]
+    fn replica_history_time_lock() -> Result<()> {
+        let replicas = SecretKeySet::random(0, &mut OsRng);
+        let clock = SimulatedClock::new(999);
+        let mut validator =
+            ReplicaHistoryValidator::new(replicas.public_keys()).with_clock(clock.clone());
+        let actor_secret = SecretKey::random();
+        let actor = PublicKey::Bls(actor_secret.public_key());
+        let funder_secret = SecretKey::random();
+        let funder = PublicKey::Bls(funder_secret.public_key());
+        let funding = transfer_with(funder, actor, 0);
+        validator.validate(&propagated_by(&funding, &funder_secret, &replicas)?)?;
+
+        let transfer = Transfer {
+            not_before: 1_000,
+            ..transfer_with(actor, funder, 0)
+        };
+        let event = registered_by(&transfer, &actor_secret, &replicas)?;
+        assert_eq!(
+            validator.validate(&event),
+            Err(Error::TransferTimeLocked(1_000))
+        );
+        clock.advance(1);
+        validator.validate(&event)?;
+
+        // Without a clock given, time-locks are checked against the system time.
+        let mut unclocked = ReplicaHistoryValidator::new(replicas.public_keys());
+        unclocked.validate(&propagated_by(&funding, &funder_secret, &replicas)?)?;
+        let locked = Transfer {
+            not_before: u64::MAX,
+            ..transfer_with(actor, funder, 0)
+        };
+        assert_eq!(
+            unclocked.validate(&registered_by(&locked, &actor_secret, &replicas)?),
+            Err(Error::TransferTimeLocked(u64::MAX))
+        );
+        unclocked.validate(&event)?;
+        Ok(())
+    }
+
+    #[test]
+    fn payment_intent() -> Result<()> {
+        let payer = Keypair::new_ed25519(&mut OsRng);
+        let intent = PaymentIntent {
+            payer: payer.public_key(),
+            nonce: 0,
+            amount: Token::from_nano(10),
+            token: TokenId::NATIVE,
+            recipient: Keypair::new_ed25519(&mut OsRng).public_key(),
+            schedule: PaymentSchedule {
+                start: 100,
+                interval: 10,
+            },
+            max_executions: 2,
+            msg: "rent".to_string(),
+        };
+        assert!(!intent.is_due(0, 99));
+        assert!(intent.is_due(0, 100));
+        assert!(!intent.is_due(1, 109));
+        assert!(intent.is_due(1, 110));
+        assert!(!intent.is_due(2, u64::MAX));
+
+        // The nonce tells apart intents which are otherwise the same.
+        let mut again = intent.clone();
+        again.nonce = 1;
+        assert_ne!(intent.id()?, again.id()?);
+
+        let signed = intent.clone().sign(&payer)?;
+        PaymentIntent::verify(&signed)?;
+
+        // Only the payer may sign the intent.
+        let other = Keypair::new_ed25519(&mut OsRng);
+        assert_eq!(intent.clone().sign(&other), Err(Error::InvalidSignature));
+        let forged = SignedWrite::new(intent, &other)?;
+        forged.verify()?;
+        assert_eq!(PaymentIntent::verify(&forged), Err(Error::InvalidSignature));
+        Ok(())
+    }
+
+    #[test]
+    fn signed_balance_and_history() -> Result<()> {
+        let replicas = SecretKeySet::random(1, &mut OsRng);
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let share = |data: &[u8], index: usize| SignatureShare {
+            index,
+            share: replicas.secret_key_share(index).sign(data),
+        };
+
+        let amount = Token::from_nano(10);
+        let token = TokenId::NATIVE;
+        let mut balance = SignedBalance {
+            owner,
+            amount,
+            at_version: 3,
+            replica_sig: share(&utils::serialise(&(&owner, &amount, 3u64, &token))?, 1),
+            token,
+        };
+        balance.verify(&replicas.public_keys())?;
+        // A share from other Replicas, or moved to another index, is rejected.
+        let others = SecretKeySet::random(1, &mut OsRng);
+        assert_eq!(
+            balance.verify(&others.public_keys()),
+            Err(Error::InvalidSignature)
+        );
+        balance.replica_sig.index = 0;
+        assert_eq!(
+            balance.verify(&replicas.public_keys()),
+            Err(Error::InvalidSignature)
+        );
+        balance.replica_sig.index = 1;
+        // So is a share over the balance of another token.
+        let test_token = TokenId(XorName([1; 32]));
+        let other_token = SignedBalance {
+            token: test_token,
+            ..balance.clone()
+        };
+        assert_eq!(
+            other_token.verify(&replicas.public_keys()),
+            Err(Error::InvalidSignature)
+        );
+        // Or over another balance.
+        balance.amount = Token::from_nano(1_000);
+        assert_eq!(
+            balance.verify(&replicas.public_keys()),
+            Err(Error::InvalidSignature)
+        );
+
+        let history = ActorHistory::empty();
+        let mut signed = SignedHistory {
+            owner,
+            history: history.clone(),
+            at_version: 0,
+            replica_sig: share(&utils::serialise(&(&owner, &history, 0u64, &token))?, 0),
+            token,
+        };
+        signed.verify(&replicas.public_keys())?;
+        signed.token = test_token;
+        assert_eq!(
+            signed.verify(&replicas.public_keys()),
+            Err(Error::InvalidSignature)
+        );
+        signed.token = token;
+        signed.at_version = 1;
+        assert_eq!(
+            signed.verify(&replicas.public_keys()),
+            Err(Error::InvalidSignature)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+
+/// Type tag of a Map or a Sequence.
+///
+/// Tags below `TypeTag::RESERVED_MAX` are reserved for protocol types, so applications
+/// picking their own tags cannot collide with them.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub struct TypeTag(pub(crate) u64);
+
+impl TypeTag {
+    /// Tags strictly below this value are reserved for protocol types.
+    pub const RESERVED_MAX: u64 = 10_000;
+    /// Tag of the Map holding a files container.
+    pub const FILES_CONTAINER: TypeTag = TypeTag(1_100);
+    /// Tag of the Map holding an NRS container.
+    pub const NRS_MAP: TypeTag = TypeTag(1_500);
+    /// Tag of the Map holding the account containers.
+    pub const ACCOUNT_CONTAINER: TypeTag = TypeTag(2_000);
+
+    /// Constructs an application tag, returning `Error::ReservedTypeTag` if the tag
+    /// falls in the reserved range.
+    pub fn new(tag: u64) -> Result<Self> {
+        if tag < Self::RESERVED_MAX {
+            Err(Error::ReservedTypeTag(tag))
+        } else {
+            Ok(Self(tag))
+        }
+    }
+
+    /// Constructs a tag in the reserved range, returning `Error::InvalidOperation`
+    /// if the tag is not reserved.
+    pub fn reserved(tag: u64) -> Result<Self> {
+        if tag < Self::RESERVED_MAX {
+            Ok(Self(tag))
+        } else {
+            Err(Error::InvalidOperation)
+        }
+    }
+
+    /// Returns true if the tag is reserved for protocol types.
+    pub fn is_reserved(self) -> bool {
+        self.0 < Self::RESERVED_MAX
+    }
+
+    /// Returns the raw tag value.
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<TypeTag> for u64 {
+    fn from(tag: TypeTag) -> Self {
+        tag.0
+    }
+}
+
+impl Display for TypeTag {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TypeTag;
+    use crate::{Error, Result};
+
+    #[test]
+    fn reserved_range_is_enforced() -> Result<()> {
+        assert_eq!(TypeTag::new(1_500), Err(Error::ReservedTypeTag(1_500)));
+        assert_eq!(TypeTag::reserved(15_000), Err(Error::InvalidOperation));
+        assert_eq!(TypeTag::reserved(1_500)?, TypeTag::NRS_MAP);
+        assert!(TypeTag::NRS_MAP.is_reserved());
+
+        let tag = TypeTag::new(15_000)?;
+        assert!(!tag.is_reserved());
+        assert_eq!(u64::from(tag), 15_000);
+        Ok(())
+    }
+}
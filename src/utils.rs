@@ -11,6 +11,7 @@ use crate::errors::convert_bincode_error;
 use crate::{Error, Result};
 use multibase::{self, Base};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use xor_name::XorName;
 
 /// Wrapper for raw bincode::serialise.
 pub fn serialise<T: Serialize>(data: &T) -> Result<Vec<u8>> {
@@ -25,6 +26,31 @@ where
     bincode::deserialize(bytes).map_err(convert_bincode_error)
 }
 
+/// Serialises to CBOR, a self-describing format which is easier to inspect
+/// than bincode when bridging to other languages.
+#[cfg(feature = "cbor")]
+pub fn to_cbor<T: Serialize>(data: &T) -> Result<Vec<u8>> {
+    serde_cbor::to_vec(data).map_err(|err| Error::Serialisation(err.to_string()))
+}
+
+/// Deserialises from CBOR.
+#[cfg(feature = "cbor")]
+pub fn from_cbor<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    serde_cbor::from_slice(bytes).map_err(|err| Error::Serialisation(err.to_string()))
+}
+
+/// Serialises to MessagePack, keeping struct field names so the output is self-describing.
+#[cfg(feature = "msgpack")]
+pub fn to_msgpack<T: Serialize>(data: &T) -> Result<Vec<u8>> {
+    rmp_serde::to_vec_named(data).map_err(|err| Error::Serialisation(err.to_string()))
+}
+
+/// Deserialises from MessagePack.
+#[cfg(feature = "msgpack")]
+pub fn from_msgpack<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    rmp_serde::from_slice(bytes).map_err(|err| Error::Serialisation(err.to_string()))
+}
+
 /// Wrapper for z-Base-32 multibase::encode.
 pub(crate) fn encode<T: Serialize>(data: &T) -> Result<String> {
     let bytes = serialise(&data)?;
@@ -43,3 +69,101 @@ pub(crate) fn decode<I: AsRef<str>, O: DeserializeOwned>(encoded: I) -> Result<O
     }
     Ok(deserialise(&decoded).map_err(|e| Error::FailedToParse(e.to_string()))?)
 }
+
+/// Derives the `XorName` at `index` from a seed name, e.g. the names of the per-app
+/// containers of an account. The result is the SHA3-256 hash of the seed bytes followed
+/// by the big-endian bytes of the index, i.e. `XorName::from_content(&[&seed.0, &index])`.
+pub fn derive_xor_name(seed: &XorName, index: u64) -> XorName {
+    XorName::from_content(&[&seed.0, &index.to_be_bytes()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::derive_xor_name;
+    #[cfg(all(
+        feature = "blob",
+        feature = "map",
+        any(feature = "cbor", feature = "msgpack")
+    ))]
+    use crate::{Blob, Keypair, Map, PublicBlob, Result, SeqMap};
+    #[cfg(all(
+        feature = "blob",
+        feature = "map",
+        any(feature = "cbor", feature = "msgpack")
+    ))]
+    use rand::rngs::OsRng;
+    use xor_name::XorName;
+
+    #[test]
+    fn derived_xor_names() {
+        // SHA3-256 of 32 bytes of 1 followed by the 8 big-endian bytes of 1.
+        let seed = XorName([1; 32]);
+        assert_eq!(
+            hex::encode(derive_xor_name(&seed, 1).0),
+            "357614d1e50e698490427c536158d7cbac3e7fee85a8fee24c047535492fd6ba"
+        );
+        let mut content = seed.0.to_vec();
+        content.extend_from_slice(&1_u64.to_be_bytes());
+        assert_eq!(
+            derive_xor_name(&seed, 1),
+            XorName::from_content(&[&content])
+        );
+
+        // Each index and each seed derive their own name.
+        assert_ne!(derive_xor_name(&seed, 0), derive_xor_name(&seed, 1));
+        assert_ne!(derive_xor_name(&seed, 1), derive_xor_name(&seed, 1 << 56));
+        assert_ne!(
+            derive_xor_name(&seed, 1),
+            derive_xor_name(&XorName([2; 32]), 1)
+        );
+    }
+
+    #[cfg(all(
+        feature = "blob",
+        feature = "map",
+        any(feature = "cbor", feature = "msgpack")
+    ))]
+    fn sample_data() -> (Blob, Map) {
+        let owner = Keypair::new_ed25519(&mut OsRng).public_key();
+        let blob = PublicBlob::new(b"some data".to_vec()).into();
+        let map = SeqMap::new(XorName::random(), 15000, owner).into();
+        (blob, map)
+    }
+
+    #[cfg(all(feature = "blob", feature = "map", feature = "cbor"))]
+    #[test]
+    fn cbor_roundtrip() -> Result<()> {
+        let (blob, map) = sample_data();
+        assert_eq!(super::from_cbor::<Blob>(&super::to_cbor(&blob)?)?, blob);
+        assert_eq!(super::from_cbor::<Map>(&super::to_cbor(&map)?)?, map);
+        Ok(())
+    }
+
+    #[cfg(all(feature = "blob", feature = "map", feature = "msgpack"))]
+    #[test]
+    fn msgpack_roundtrip() -> Result<()> {
+        let (blob, map) = sample_data();
+        assert_eq!(
+            super::from_msgpack::<Blob>(&super::to_msgpack(&blob)?)?,
+            blob
+        );
+        assert_eq!(super::from_msgpack::<Map>(&super::to_msgpack(&map)?)?, map);
+        Ok(())
+    }
+
+    #[cfg(all(
+        feature = "blob",
+        feature = "map",
+        feature = "cbor",
+        feature = "msgpack"
+    ))]
+    #[test]
+    fn cross_format_roundtrip() -> Result<()> {
+        let (blob, _) = sample_data();
+        let from_bincode: Blob = super::deserialise(&super::serialise(&blob)?)?;
+        let from_cbor: Blob = super::from_cbor(&super::to_cbor(&from_bincode)?)?;
+        let from_msgpack: Blob = super::from_msgpack(&super::to_msgpack(&from_cbor)?)?;
+        assert_eq!(from_msgpack, blob);
+        Ok(())
+    }
+}
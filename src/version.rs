@@ -0,0 +1,130 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Protocol versioning of the serialised data types.
+//!
+//! Versioned payloads are the bincode serialisation of the value, prefixed with the
+//! big-endian `u16` protocol version it was serialised with.
+//!
+//! Bincode cannot read a layout other than its own, so every change of the bincode layout
+//! of a type bumps `PROTOCOL_VERSION`, and `MIN_SUPPORTED_VERSION` with it, in the same change.
+//!
+//! Changes of the bincode layout by version:
+//! - 2: Sequence data records whether it has been deleted.
+//! - 3: Blobs carry their storage class.
+//! - 4: Transfers, debits and credits carry the token they are in.
+//! - 5: Transfers and debits carry the time before which they may not be registered.
+//! - 6: Sequence policies carry limits on their entries.
+//! - 7: Signed writes carry an op id, after their signature.
+//! - 8: Sequences carry their metadata, and metadata writes their clock.
+//! - 9: Maps keep the log of their permission ops.
+//! - 10: Access denied errors carry the action denied and the address of the data.
+//! - 11: Signed responses carry the address of the data queried.
+//! - 12: Signed balances and histories carry their token.
+
+use crate::{utils, Error, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use std::convert::TryInto;
+
+/// Version of the protocol implemented by this crate.
+pub const PROTOCOL_VERSION: u16 = 12;
+
+/// Oldest protocol version this crate can still deserialise.
+pub const MIN_SUPPORTED_VERSION: u16 = 12;
+
+const VERSION_LEN: usize = 2;
+
+/// Returns true if payloads serialised with `their_version` can be read by this crate,
+/// i.e. if it is between `MIN_SUPPORTED_VERSION` and `PROTOCOL_VERSION`.
+pub fn is_compatible(their_version: u16) -> bool {
+    (MIN_SUPPORTED_VERSION..=PROTOCOL_VERSION).contains(&their_version)
+}
+
+/// Returns the version to use when talking to a peer implementing `their_version`,
+/// or `None` if there is no version both sides support.
+pub fn negotiate_version(their_version: u16) -> Option<u16> {
+    let version = their_version.min(PROTOCOL_VERSION);
+    if is_compatible(version) {
+        Some(version)
+    } else {
+        None
+    }
+}
+
+/// Serialises the value, prefixed with `PROTOCOL_VERSION`.
+pub fn serialise_versioned<T: Serialize>(data: &T) -> Result<Vec<u8>> {
+    let mut bytes = PROTOCOL_VERSION.to_be_bytes().to_vec();
+    bytes.extend(utils::serialise(data)?);
+    Ok(bytes)
+}
+
+/// Deserialises a value serialised with `serialise_versioned`, returning
+/// `Error::UnsupportedVersion` if its version is not compatible.
+pub fn deserialise_versioned<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    let version = payload_version(bytes)?;
+    if !is_compatible(version) {
+        return Err(Error::UnsupportedVersion(version));
+    }
+    utils::deserialise(&bytes[VERSION_LEN..])
+}
+
+/// Returns the protocol version a versioned payload was serialised with.
+pub fn payload_version(bytes: &[u8]) -> Result<u16> {
+    bytes
+        .get(..VERSION_LEN)
+        .and_then(|version| version.try_into().ok())
+        .map(u16::from_be_bytes)
+        .ok_or_else(|| Error::FailedToParse("Missing protocol version".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Token;
+
+    #[test]
+    fn versioned_roundtrip() -> Result<()> {
+        let token = Token::from_nano(1);
+        let bytes = serialise_versioned(&token)?;
+        assert_eq!(payload_version(&bytes)?, PROTOCOL_VERSION);
+        assert_eq!(deserialise_versioned::<Token>(&bytes)?, token);
+        Ok(())
+    }
+
+    #[test]
+    fn incompatible_versions_are_rejected() -> Result<()> {
+        let mut bytes = serialise_versioned(&Token::from_nano(1))?;
+        let future = PROTOCOL_VERSION + 1;
+        bytes[..VERSION_LEN].copy_from_slice(&future.to_be_bytes());
+        assert_eq!(
+            deserialise_versioned::<Token>(&bytes),
+            Err(Error::UnsupportedVersion(future))
+        );
+        assert!(deserialise_versioned::<Token>(&[0]).is_err());
+
+        assert!(is_compatible(PROTOCOL_VERSION));
+        assert!(!is_compatible(MIN_SUPPORTED_VERSION - 1));
+        assert_eq!(negotiate_version(future), Some(PROTOCOL_VERSION));
+        assert_eq!(negotiate_version(MIN_SUPPORTED_VERSION - 1), None);
+        Ok(())
+    }
+
+    #[test]
+    fn previous_layouts_are_rejected() -> Result<()> {
+        let mut bytes = serialise_versioned(&Token::from_nano(1))?;
+        let previous = PROTOCOL_VERSION - 1;
+        bytes[..VERSION_LEN].copy_from_slice(&previous.to_be_bytes());
+        assert_eq!(
+            deserialise_versioned::<Token>(&bytes),
+            Err(Error::UnsupportedVersion(previous))
+        );
+        assert_eq!(MIN_SUPPORTED_VERSION, PROTOCOL_VERSION);
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! WebAssembly bindings, enabled by the `wasm` feature.
+//!
+//! Wraps the key types for use from JavaScript through `wasm-bindgen`. Keys and signatures
+//! cross the boundary in their bincode serialisation, so they can be passed straight into
+//! messages built on the JavaScript side. Errors are thrown as their display string.
+
+use crate::{utils, Error, Keypair, PublicKey, Signature, Token};
+use rand::rngs::OsRng;
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+use xor_name::{XorName, XOR_NAME_LEN};
+
+fn to_js(error: Error) -> JsValue {
+    JsValue::from_str(&error.to_string())
+}
+
+// Checks the length apart from the binding, as a `JsValue` cannot be built off wasm.
+fn xor_name_from_bytes(bytes: &[u8]) -> crate::Result<XorName> {
+    if bytes.len() != XOR_NAME_LEN {
+        return Err(Error::FailedToParse(
+            "XorName must be 32 bytes long".to_string(),
+        ));
+    }
+    let mut name = [0; XOR_NAME_LEN];
+    name.copy_from_slice(bytes);
+    Ok(XorName(name))
+}
+
+/// A network address.
+#[wasm_bindgen(js_name = XorName)]
+pub struct JsXorName(XorName);
+
+#[wasm_bindgen(js_class = XorName)]
+impl JsXorName {
+    /// Constructs a name from its 32 bytes.
+    #[wasm_bindgen(constructor)]
+    pub fn new(bytes: &[u8]) -> Result<JsXorName, JsValue> {
+        xor_name_from_bytes(bytes).map(Self).map_err(to_js)
+    }
+
+    /// Generates a random name.
+    pub fn random() -> JsXorName {
+        Self(XorName::random())
+    }
+
+    /// Returns the 32 bytes of the name.
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+
+    /// Returns the abbreviated hex form of the name.
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_js_string(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// A public key.
+#[wasm_bindgen(js_name = PublicKey)]
+pub struct JsPublicKey(PublicKey);
+
+#[wasm_bindgen(js_class = PublicKey)]
+impl JsPublicKey {
+    /// Deserialises a key.
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(bytes: &[u8]) -> Result<JsPublicKey, JsValue> {
+        utils::deserialise(bytes).map(Self).map_err(to_js)
+    }
+
+    /// Serialises the key.
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, JsValue> {
+        utils::serialise(&self.0).map_err(to_js)
+    }
+
+    /// Returns the name derived from the key.
+    pub fn name(&self) -> JsXorName {
+        JsXorName(self.0.into())
+    }
+
+    /// Verifies a serialised signature over `data`, throwing if it is invalid.
+    pub fn verify(&self, signature: &[u8], data: &[u8]) -> Result<(), JsValue> {
+        let signature: Signature = utils::deserialise(signature).map_err(to_js)?;
+        self.0.verify(&signature, data).map_err(to_js)
+    }
+
+    /// Returns the abbreviated hex form of the key.
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_js_string(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// A keypair, for signing messages in the browser.
+#[wasm_bindgen(js_name = Keypair)]
+pub struct JsKeypair(Keypair);
+
+#[wasm_bindgen(js_class = Keypair)]
+impl JsKeypair {
+    /// Generates a random Ed25519 keypair.
+    #[wasm_bindgen(js_name = newEd25519)]
+    pub fn new_ed25519() -> JsKeypair {
+        Self(Keypair::new_ed25519(&mut OsRng))
+    }
+
+    /// Returns the public key.
+    #[wasm_bindgen(js_name = publicKey)]
+    pub fn public_key(&self) -> JsPublicKey {
+        JsPublicKey(self.0.public_key())
+    }
+
+    /// Signs `data`, returning the serialised signature.
+    pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>, JsValue> {
+        utils::serialise(&self.0.sign(data)).map_err(to_js)
+    }
+}
+
+/// An amount of tokens.
+#[wasm_bindgen(js_name = Token)]
+pub struct JsToken(Token);
+
+#[wasm_bindgen(js_class = Token)]
+impl JsToken {
+    /// Constructs an amount from a number of nano tokens.
+    #[wasm_bindgen(js_name = fromNano)]
+    pub fn from_nano(nano: u64) -> JsToken {
+        Self(Token::from_nano(nano))
+    }
+
+    /// Parses a decimal amount, e.g. `"1.5"`.
+    #[wasm_bindgen(js_name = fromString)]
+    pub fn from_string(amount: &str) -> Result<JsToken, JsValue> {
+        Token::from_str(amount).map(Self).map_err(to_js)
+    }
+
+    /// Returns the amount as a number of nano tokens.
+    #[wasm_bindgen(js_name = asNano)]
+    pub fn as_nano(&self) -> u64 {
+        self.0.as_nano()
+    }
+
+    /// Returns the decimal form of the amount.
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_js_string(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+// Host-side tests, which only take the paths not throwing, as a thrown `JsValue`
+// cannot be built off wasm.
+#[cfg(test)]
+mod tests {
+    use super::{xor_name_from_bytes, JsKeypair, JsPublicKey, JsToken, JsXorName};
+    use crate::{Error, Result};
+    use xor_name::XOR_NAME_LEN;
+
+    #[test]
+    fn xor_name_length() -> Result<()> {
+        let bytes = [7; XOR_NAME_LEN];
+        assert_eq!(xor_name_from_bytes(&bytes)?.0, bytes);
+        assert_eq!(
+            JsXorName::new(&bytes).map(|name| name.to_bytes()).ok(),
+            Some(bytes.to_vec())
+        );
+        for len in &[0, XOR_NAME_LEN - 1, XOR_NAME_LEN + 1] {
+            assert!(matches!(
+                xor_name_from_bytes(&vec![7; *len]),
+                Err(Error::FailedToParse(_))
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn keypair_signs_and_verifies() {
+        let keypair = JsKeypair::new_ed25519();
+        let public_key = keypair.public_key();
+        let signature = keypair
+            .sign(b"data")
+            .unwrap_or_else(|_| panic!("signing failed"));
+        assert!(public_key.verify(&signature, b"data").is_ok());
+
+        let bytes = public_key
+            .to_bytes()
+            .unwrap_or_else(|_| panic!("serialising failed"));
+        let decoded =
+            JsPublicKey::from_bytes(&bytes).unwrap_or_else(|_| panic!("deserialising failed"));
+        assert!(decoded.verify(&signature, b"data").is_ok());
+        assert_eq!(decoded.name().to_bytes(), public_key.name().to_bytes());
+    }
+
+    #[test]
+    fn token_parses_and_formats() {
+        let token = JsToken::from_string("1.5").unwrap_or_else(|_| panic!("parsing failed"));
+        assert_eq!(token.as_nano(), 1_500_000_000);
+        assert_eq!(token.to_js_string(), "1.500000000");
+        assert_eq!(JsToken::from_nano(1).to_js_string(), "0.000000001");
+    }
+}